@@ -0,0 +1,19 @@
+use aoc_naalunth_2019::util::intcode::{cfg::control_flow_graph, load_intcode_program};
+use std::error::Error;
+
+type Word = i128;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let program = args
+        .next()
+        .ok_or("usage: intcode_cfg <day number | program file> [output file]")?;
+    let cfg = control_flow_graph(&load_intcode_program::<Word>(&program)?)
+        .ok_or_else(|| format!("{}: no instruction can be decoded at address 0", program))?;
+    let dot = cfg.to_dot();
+    match args.next() {
+        Some(path) => std::fs::write(&path, dot).map_err(|err| format!("{}: {}", path, err))?,
+        None => print!("{}", dot),
+    }
+    Ok(())
+}
@@ -0,0 +1,391 @@
+use aoc_naalunth_2019::util::intcode::{
+    disassembler::disassemble_at, load_intcode_program, profile::DEFAULT_HOTSPOTS, Address,
+    Emulator, RunResult,
+};
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+type Word = i128;
+
+const HELP: &str = "\
+commands:
+  break <addr>          stop before executing the instruction at <addr>
+  delete <addr>         remove a breakpoint
+  watch <addr>          stop after an instruction writes to <addr>
+  unwatch <addr>        remove a watchpoint
+  step [n]              execute n instructions (default 1)
+  continue              run until a breakpoint, watchpoint, input request or halt
+  x[/n] <addr>          print n memory words starting at <addr> (default 1)
+  set mem <addr> <val>  write <val> to memory
+  input <val>...        queue input values
+  info regs             show instruction pointer, relative base and pending input
+  info break            list breakpoints and watchpoints
+  disas [addr] [n]      disassemble n instructions at addr (default: 8 at ip)
+  profile on|off        start or stop counting executions and memory accesses
+  profile [n]           show the n hottest instructions and memory words (default 20)
+  restart               reload the program and clear pending input
+  save <file>           write a snapshot of the machine state
+  load <file>           restore a snapshot written by `save`
+  help                  show this message
+  quit                  exit";
+
+fn parse_number<T: std::str::FromStr>(arg: Option<&str>, what: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("missing {}", what))?;
+    arg.parse()
+        .map_err(|_| format!("invalid {} `{}`", what, arg))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Break(Address),
+    Delete(Address),
+    Watch(Address),
+    Unwatch(Address),
+    Step(usize),
+    Continue,
+    SetMemory(Address, Word),
+    Input(Vec<Word>),
+    InfoRegisters,
+    InfoBreakpoints,
+    Disassemble(Option<Address>, usize),
+    ProfileOn,
+    ProfileOff,
+    ProfileReport(usize),
+    Restart,
+    Save(String),
+    Load(String),
+    Help,
+    Quit,
+    /// `x/count address`, where all `count` addresses exist.
+    Examine {
+        address: Address,
+        count: usize,
+    },
+}
+
+/// Parses one command line, returning `None` for an empty one.
+fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let mut args = line.split_whitespace();
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Ok(None),
+    };
+    let optional_number = |arg: Option<&str>, what: &str, default| match arg {
+        Some(arg) => parse_number(Some(arg), what),
+        None => Ok(default),
+    };
+    let command = match command {
+        "break" | "b" => Command::Break(parse_number(args.next(), "address")?),
+        "delete" | "d" => Command::Delete(parse_number(args.next(), "address")?),
+        "watch" => Command::Watch(parse_number(args.next(), "address")?),
+        "unwatch" => Command::Unwatch(parse_number(args.next(), "address")?),
+        "step" | "s" => Command::Step(optional_number(args.next(), "count", 1)?),
+        "continue" | "c" => Command::Continue,
+        "set" => {
+            if args.next() != Some("mem") {
+                return Err("usage: set mem <addr> <val>".to_owned());
+            }
+            let address = parse_number(args.next(), "address")?;
+            let value = parse_number(args.next(), "value")?;
+            Command::SetMemory(address, value)
+        }
+        "input" | "i" => Command::Input(
+            args.map(|arg| parse_number(Some(arg), "value"))
+                .collect::<Result<_, _>>()?,
+        ),
+        "info" => match args.next() {
+            Some("regs") | Some("r") => Command::InfoRegisters,
+            Some("break") | Some("b") => Command::InfoBreakpoints,
+            _ => return Err("usage: info regs|break".to_owned()),
+        },
+        "disas" => {
+            let address = match args.next() {
+                Some(address) => Some(parse_number(Some(address), "address")?),
+                None => None,
+            };
+            Command::Disassemble(address, optional_number(args.next(), "count", 8)?)
+        }
+        "profile" => match args.next() {
+            Some("on") => Command::ProfileOn,
+            Some("off") => Command::ProfileOff,
+            top => Command::ProfileReport(optional_number(top, "count", DEFAULT_HOTSPOTS)?),
+        },
+        "restart" => Command::Restart,
+        "save" => Command::Save(args.next().ok_or("missing file")?.to_owned()),
+        "load" => Command::Load(args.next().ok_or("missing file")?.to_owned()),
+        "help" | "h" => Command::Help,
+        "quit" | "q" => Command::Quit,
+        _ if command.starts_with('x') => {
+            let count = match command.strip_prefix("x/") {
+                Some(count) => parse_number(Some(count), "count")?,
+                None if command == "x" => 1,
+                None => return Err(format!("unknown command `{}`", command)),
+            };
+            let address: Address = parse_number(args.next(), "address")?;
+            if count > 0 && address.checked_add(count - 1).is_none() {
+                return Err(format!(
+                    "{} words at {} run past the last address",
+                    count, address
+                ));
+            }
+            Command::Examine { address, count }
+        }
+        _ => return Err(format!("unknown command `{}`, try `help`", command)),
+    };
+    Ok(Some(command))
+}
+
+struct Debugger {
+    program: Vec<Word>,
+    emulator: Emulator<Word>,
+}
+
+impl Debugger {
+    fn new(program: Vec<Word>) -> Self {
+        Self {
+            emulator: Emulator::new(program.clone()),
+            program,
+        }
+    }
+
+    fn print_disassembly(&self, address: Address, count: usize) {
+        let emulator = &self.emulator;
+        for line in disassemble_at(|a| emulator.read_memory(a), address, count) {
+            let marker = if line.address() == emulator.instruction_pointer() {
+                "=>"
+            } else {
+                "  "
+            };
+            println!("{}{}", marker, line);
+        }
+    }
+
+    /// Prints a stop event, returning whether execution should keep going.
+    fn report(&self, result: RunResult<Word>) -> bool {
+        match result {
+            RunResult::Output(value) => {
+                println!("output: {}", value);
+                return true;
+            }
+            RunResult::OutOfFuel => return true,
+            RunResult::Halt => println!("program halted"),
+            RunResult::InputRequest => println!("waiting for input"),
+            RunResult::Breakpoint(address) => println!("breakpoint at {}", address),
+            RunResult::Watchpoint(address) => println!(
+                "watchpoint: [{}] = {}",
+                address,
+                self.emulator.read_memory(address)
+            ),
+        }
+        false
+    }
+
+    fn step(&mut self, count: usize) -> Result<(), String> {
+        for _ in 0..count {
+            let result = self.emulator.step().map_err(|err| err.to_string())?;
+            if !self.report(result) {
+                break;
+            }
+        }
+        self.print_disassembly(self.emulator.instruction_pointer(), 1);
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), String> {
+        loop {
+            let result = self.emulator.run().map_err(|err| err.to_string())?;
+            if !self.report(result) {
+                break;
+            }
+        }
+        self.print_disassembly(self.emulator.instruction_pointer(), 1);
+        Ok(())
+    }
+
+    /// Runs one command line, returning `false` when the user asked to quit.
+    fn execute(&mut self, line: &str) -> Result<bool, String> {
+        let command = match parse_command(line)? {
+            Some(command) => command,
+            None => return Ok(true),
+        };
+        match command {
+            Command::Break(address) => {
+                self.emulator.add_breakpoint(address);
+            }
+            Command::Delete(address) => {
+                if !self.emulator.remove_breakpoint(address) {
+                    return Err(format!("no breakpoint at {}", address));
+                }
+            }
+            Command::Watch(address) => {
+                self.emulator.add_watchpoint(address);
+            }
+            Command::Unwatch(address) => {
+                if !self.emulator.remove_watchpoint(address) {
+                    return Err(format!("no watchpoint at {}", address));
+                }
+            }
+            Command::Step(count) => self.step(count)?,
+            Command::Continue => self.resume()?,
+            Command::SetMemory(address, value) => self.emulator.write_memory(address, value),
+            Command::Input(values) => {
+                for value in values {
+                    self.emulator
+                        .try_push_input(value)
+                        .map_err(|err| err.to_string())?;
+                }
+            }
+            Command::InfoRegisters => {
+                println!("ip: {}", self.emulator.instruction_pointer());
+                println!("rb: {}", self.emulator.relative_base_offset());
+                println!("pending input: {}", self.emulator.pending_input());
+                println!("halted: {}", self.emulator.is_halted());
+            }
+            Command::InfoBreakpoints => {
+                for address in self.emulator.breakpoints() {
+                    println!("breakpoint {}", address);
+                }
+                for address in self.emulator.watchpoints() {
+                    println!("watchpoint {}", address);
+                }
+            }
+            Command::Disassemble(address, count) => {
+                let address = address.unwrap_or_else(|| self.emulator.instruction_pointer());
+                self.print_disassembly(address, count);
+            }
+            Command::ProfileOn => self.emulator.start_profile(),
+            Command::ProfileOff => {
+                self.emulator.stop_profile();
+            }
+            Command::ProfileReport(top) => {
+                let emulator = &self.emulator;
+                let profile = emulator.profile().ok_or("profiling is off")?;
+                print!("{}", profile.report(|a| emulator.read_memory(a), top));
+            }
+            Command::Restart => {
+                let breakpoints = self.emulator.breakpoints().collect::<Vec<_>>();
+                let watchpoints = self.emulator.watchpoints().collect::<Vec<_>>();
+                self.emulator = Emulator::new(self.program.clone());
+                breakpoints.into_iter().for_each(|a| {
+                    self.emulator.add_breakpoint(a);
+                });
+                watchpoints.into_iter().for_each(|a| {
+                    self.emulator.add_watchpoint(a);
+                });
+            }
+            Command::Save(path) => {
+                self.emulator
+                    .save_state_to_file(&path)
+                    .map_err(|err| format!("{}: {}", path, err))?;
+            }
+            Command::Load(path) => {
+                let mut emulator = Emulator::load_state_from_file(&path)
+                    .map_err(|err| format!("{}: {}", path, err))?;
+                for address in self.emulator.breakpoints() {
+                    emulator.add_breakpoint(address);
+                }
+                for address in self.emulator.watchpoints() {
+                    emulator.add_watchpoint(address);
+                }
+                self.emulator = emulator;
+                self.print_disassembly(self.emulator.instruction_pointer(), 1);
+            }
+            Command::Help => println!("{}", HELP),
+            Command::Quit => return Ok(false),
+            Command::Examine { address, count } => {
+                for address in (0..count).map(|offset| address + offset) {
+                    println!("{:>6}: {}", address, self.emulator.read_memory(address));
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arg = std::env::args()
+        .nth(1)
+        .ok_or("usage: intcode_debugger <day number | program file>")?;
+    let mut debugger = Debugger::new(load_intcode_program(&arg)?);
+    debugger.print_disassembly(0, 1);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(icdb) ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        match debugger.execute(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(message) => println!("error: {}", message),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("  "), Ok(None));
+        assert_eq!(parse_command("b 12"), Ok(Some(Command::Break(12))));
+        assert_eq!(parse_command("step"), Ok(Some(Command::Step(1))));
+        assert_eq!(
+            parse_command("set mem 5 -3"),
+            Ok(Some(Command::SetMemory(5, -3)))
+        );
+        assert_eq!(
+            parse_command("input 1 2"),
+            Ok(Some(Command::Input(vec![1, 2])))
+        );
+        assert_eq!(
+            parse_command("disas"),
+            Ok(Some(Command::Disassemble(None, 8)))
+        );
+        assert_eq!(
+            parse_command("profile 5"),
+            Ok(Some(Command::ProfileReport(5)))
+        );
+        assert_eq!(
+            parse_command("x/4 100"),
+            Ok(Some(Command::Examine {
+                address: 100,
+                count: 4
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_command_errors() {
+        assert_eq!(
+            parse_command("x/5 18446744073709551615"),
+            Err("5 words at 18446744073709551615 run past the last address".to_owned())
+        );
+        assert_eq!(
+            parse_command("x 18446744073709551615"),
+            Ok(Some(Command::Examine {
+                address: Address::MAX,
+                count: 1
+            }))
+        );
+        assert_eq!(parse_command("break"), Err("missing address".to_owned()));
+        assert_eq!(
+            parse_command("step many"),
+            Err("invalid count `many`".to_owned())
+        );
+        assert_eq!(
+            parse_command("xyz"),
+            Err("unknown command `xyz`".to_owned())
+        );
+        assert_eq!(
+            parse_command("info"),
+            Err("usage: info regs|break".to_owned())
+        );
+    }
+}
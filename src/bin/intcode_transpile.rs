@@ -0,0 +1,21 @@
+use aoc_naalunth_2019::util::intcode::{load_intcode_program, transpiler::transpile};
+use std::error::Error;
+
+type Word = i128;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let program = args
+        .next()
+        .ok_or("usage: intcode_transpile <day number | program file> [output file]")?;
+    let translation = transpile(&load_intcode_program::<Word>(&program)?);
+    match args.next() {
+        Some(path) => {
+            let command = format!("cargo run --bin intcode_transpile -- {} {}", program, path);
+            let source = translation.with_command(command).to_string();
+            std::fs::write(&path, source).map_err(|err| format!("{}: {}", path, err))?
+        }
+        None => print!("{}", translation),
+    }
+    Ok(())
+}
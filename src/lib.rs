@@ -5,6 +5,13 @@ extern crate smallvec;
 use aoc_runner_derive::aoc_lib;
 
 pub mod solutions {
+    use crate::util::intcode::Tier;
+
+    /// The tier all intcode puzzles run on. Compiled code is a bit faster for the long-running
+    /// machines of days 9 and 13, but the puzzles that fork lots of short-lived machines lose
+    /// more to compiling than they gain, so the interpreter wins overall.
+    pub const INTCODE_TIER: Tier = Tier::Interpreter;
+
     pub mod day01;
     pub mod day02;
     pub mod day03;
@@ -22,7 +29,7 @@ pub mod solutions {
     pub mod day15;
 }
 
-mod util {
+pub mod util {
     pub mod intcode;
     pub mod parsers;
 }
@@ -1,4 +1,5 @@
-use crate::util::intcode::{parse_intcode_text, Emulator, RunResult};
+use super::INTCODE_TIER;
+use crate::util::intcode::{parse_intcode_text, Emulator};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::error::Error;
 
@@ -13,12 +14,10 @@ pub fn generator(input: &[u8]) -> Result<GeneratorOutput, Box<dyn Error>> {
 
 fn run_program(program: &[Word], id: Word) -> Word {
     let mut emulator = Emulator::new(program.to_owned());
-    emulator.push_input(id);
-    let mut result = 0;
-    while let RunResult::Output(val) = emulator.run() {
-        result = val;
-    }
-    result
+    emulator.set_checked_arithmetic(true);
+    emulator.set_tier(INTCODE_TIER);
+    let outputs = emulator.run_to_completion(vec![id]).unwrap();
+    *outputs.last().unwrap()
 }
 
 #[aoc(day5, part1)]
@@ -1,8 +1,11 @@
-use crate::util::intcode::{parse_intcode_text, Emulator, RunResult};
+use super::INTCODE_TIER;
+use crate::util::intcode::{
+    network::{Network, NetworkReport},
+    parse_intcode_text, Emulator,
+};
 use aoc_runner_derive::{aoc, aoc_generator};
-use arrayvec::ArrayVec;
 use itertools::Itertools;
-use std::{error::Error, mem::replace};
+use std::error::Error;
 
 type Word = i32;
 type GeneratorOutput = Vec<Word>;
@@ -13,24 +16,42 @@ pub fn generator(input: &[u8]) -> Result<GeneratorOutput, Box<dyn Error>> {
     parse_intcode_text(input)
 }
 
+/// The amplifier program, forked once for every amplifier of every phase setting.
+fn template(original: &PartInput) -> Emulator<Word> {
+    let mut template = Emulator::new(original.to_owned());
+    template.set_checked_arithmetic(true);
+    template.set_tier(INTCODE_TIER);
+    template
+}
+
+/// One amplifier per phase setting, wired up by `wire`, with the first signal queued.
+fn amplifiers(
+    template: &Emulator<Word>,
+    phases: &[Word],
+    wire: fn(Vec<Emulator<Word>>) -> Network<Word>,
+) -> Network<Word> {
+    let mut network = wire(phases.iter().map(|_| template.fork()).collect());
+    for (id, &phase) in phases.iter().enumerate() {
+        network.push_input(id, phase).unwrap();
+    }
+    network.push_input(0, 0).unwrap();
+    network
+}
+
+/// The last signal sent by the final amplifier.
+fn final_signal(report: NetworkReport<Word>) -> Word {
+    assert!(report.all_halted());
+    report.machines.last().unwrap().last_output.unwrap()
+}
+
 #[aoc(day7, part1)]
 pub fn part_1(original: &PartInput) -> Word {
-    let mut memory = Some(original.to_owned());
+    let template = template(original);
     (0..=4)
         .permutations(5)
-        .map(|params| {
-            params.iter().fold(0, |acc, &param| {
-                let mut emulator = Emulator::new(replace(&mut memory, None).unwrap());
-                emulator.extend_input([param, acc].iter().cloned());
-                if let RunResult::Output(output) = emulator.run() {
-                    let mut mem = emulator.into_memory();
-                    mem.copy_from_slice(original);
-                    replace(&mut memory, Some(mem));
-                    output
-                } else {
-                    panic!()
-                }
-            })
+        .map(|phases| {
+            let mut network = amplifiers(&template, &phases, Network::chain);
+            final_signal(network.run().unwrap())
         })
         .max()
         .unwrap()
@@ -38,41 +59,36 @@ pub fn part_1(original: &PartInput) -> Word {
 
 #[aoc(day7, part2)]
 pub fn part_2(original: &PartInput) -> Word {
-    let mut memories = (0..=4)
-        .map(|_| Some(original.to_owned()))
-        .collect::<ArrayVec<[Option<Vec<Word>>; 5]>>();
+    let template = template(original);
+    (5..=9)
+        .permutations(5)
+        .map(|phases| final_signal(amplifiers(&template, &phases, Network::ring).run().unwrap()))
+        .max()
+        .unwrap()
+}
+
+#[aoc(day7, part2, uncached)]
+pub fn part_2_uncached(original: &PartInput) -> Word {
+    let mut template = template(original);
+    template.set_decode_cache(false);
     (5..=9)
         .permutations(5)
-        .map(|params| {
-            let mut emulators: Vec<Emulator<Word>> = params
-                .iter()
-                .zip(memories.iter_mut())
-                .map(|(&param, memory)| {
-                    let mut emulator = Emulator::new(replace(memory, None).unwrap());
-                    emulator.push_input(param);
-                    emulator
-                })
-                .collect::<Vec<_>>();
+        .map(|phases| {
+            let mut network = amplifiers(&template, &phases, Network::ring);
+            final_signal(network.run().unwrap())
+        })
+        .max()
+        .unwrap()
+}
 
-            let mut val = 0;
-            'feedback: loop {
-                for emulator in emulators.iter_mut() {
-                    emulator.push_input(val);
-                    val = 'run: loop {
-                        match emulator.run() {
-                            RunResult::Halt => break 'feedback,
-                            RunResult::InputRequest => panic!(),
-                            RunResult::Output(output) => break 'run output,
-                        }
-                    }
-                }
-            }
-            for (emulator, memory) in emulators.into_iter().zip(memories.iter_mut()) {
-                let mut mem = emulator.into_memory();
-                mem.copy_from_slice(original);
-                replace(memory, Some(mem));
-            }
-            val
+#[aoc(day7, part2, threaded)]
+pub fn part_2_threaded(original: &PartInput) -> Word {
+    let template = template(original);
+    (5..=9)
+        .permutations(5)
+        .map(|phases| {
+            let mut network = amplifiers(&template, &phases, Network::ring);
+            final_signal(network.run_threaded().unwrap())
         })
         .max()
         .unwrap()
@@ -1,7 +1,15 @@
-use crate::util::intcode::{parse_intcode_text, Emulator, RunResult};
+use super::INTCODE_TIER;
+use crate::util::intcode::{
+    parse_intcode_text, profile::DEFAULT_HOTSPOTS, Emulator, Profiled, Tier,
+};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::error::Error;
 
+/// BOOST translated to Rust, regenerate with
+/// `cargo run --bin intcode_transpile -- 9 src/solutions/day09/boost.rs`.
+#[rustfmt::skip]
+mod boost;
+
 type Word = i128;
 type GeneratorOutput = Vec<Word>;
 type PartInput = [Word];
@@ -11,22 +19,71 @@ pub fn generator(input: &[u8]) -> Result<GeneratorOutput, Box<dyn Error>> {
     parse_intcode_text(input)
 }
 
-fn run_program(program: &[Word], id: Word) -> Word {
+fn run_program(program: &[Word], id: Word, decode_cache: bool, tier: Tier) -> Word {
     let mut emulator = Emulator::new(program.to_owned());
-    emulator.push_input(id);
-    let mut result = 0;
-    while let RunResult::Output(val) = emulator.run() {
-        result = val;
-    }
-    result
+    emulator.set_decode_cache(decode_cache);
+    emulator.set_tier(tier);
+    let outputs = emulator.run_to_completion(vec![id]).unwrap();
+    *outputs.last().unwrap()
 }
 
 #[aoc(day9, part1)]
 pub fn part_1(input: &PartInput) -> Word {
-    run_program(input, 1)
+    run_program(input, 1, true, INTCODE_TIER)
 }
 
 #[aoc(day9, part2)]
 pub fn part_2(input: &PartInput) -> Word {
-    run_program(input, 2)
+    run_program(input, 2, true, INTCODE_TIER)
+}
+
+#[aoc(day9, part2, uncached)]
+pub fn part_2_uncached(input: &PartInput) -> Word {
+    run_program(input, 2, false, INTCODE_TIER)
+}
+
+#[aoc(day9, part2, compiled)]
+pub fn part_2_compiled(input: &PartInput) -> Word {
+    run_program(input, 2, true, Tier::Compiled)
+}
+
+#[aoc(day9, part2, profiled)]
+pub fn part_2_profiled(input: &PartInput) -> Profiled<Word> {
+    let mut emulator = Emulator::new(input.to_owned());
+    emulator.start_profile();
+    let outputs = emulator.run_to_completion(vec![2]).unwrap();
+    let profile = emulator.stop_profile().unwrap();
+    Profiled {
+        result: *outputs.last().unwrap(),
+        report: profile.report(|address| emulator.read_memory(address), DEFAULT_HOTSPOTS),
+    }
+}
+
+#[aoc(day9, part2, transpiled)]
+pub fn part_2_transpiled(input: &PartInput) -> Word {
+    assert_eq!(
+        input,
+        boost::PROGRAM,
+        "boost.rs was generated from another input"
+    );
+    let outputs = boost::Machine::new().run_to_completion(vec![2]).unwrap();
+    *outputs.last().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::transpiler::transpile;
+
+    #[test]
+    fn test_boost_module_is_current() {
+        let input = include_str!("../../input/2019/day9.txt");
+        let program = generator(input.trim().as_bytes()).unwrap();
+        let command = "cargo run --bin intcode_transpile -- 9 src/solutions/day09/boost.rs";
+        assert_eq!(
+            transpile(&program).with_command(command).to_string(),
+            include_str!("day09/boost.rs"),
+            "regenerate boost.rs with intcode_transpile"
+        );
+    }
 }
@@ -0,0 +1,1831 @@
+//! Generated by `intcode_transpile`, do not edit.
+//! Regenerate with `cargo run --bin intcode_transpile -- 9 src/solutions/day09/boost.rs`.
+//!
+//! See `util::intcode::transpiler` for how it behaves.
+
+#![allow(clippy::all, dead_code, unused_parens)]
+
+use crate::util::intcode::{Address, Emulator, EmulatorError, RunResult};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+pub type Word = i128;
+
+/// The translated program.
+pub const PROGRAM: &[Word] = &[
+    1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1101,
+    3, 0, 1000, 109, 988, 209, 12, 9, 1000, 209, 6, 209,
+    3, 203, 0, 1008, 1000, 1, 63, 1005, 63, 65, 1008, 1000,
+    2, 63, 1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58,
+    4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17,
+    104, 0, 99, 0, 0, 1101, 35, 0, 1007, 1102, 30, 1,
+    1013, 1102, 37, 1, 1017, 1101, 23, 0, 1006, 1101, 0, 32,
+    1008, 1102, 1, 29, 1000, 1101, 0, 38, 1010, 1101, 0, 24,
+    1002, 1101, 33, 0, 1003, 1101, 1, 0, 1021, 1102, 31, 1,
+    1019, 1101, 27, 0, 1014, 1102, 20, 1, 1005, 1101, 0, 0,
+    1020, 1102, 1, 892, 1027, 1101, 895, 0, 1026, 1102, 39, 1,
+    1015, 1102, 1, 370, 1029, 1102, 1, 28, 1001, 1102, 34, 1,
+    1012, 1101, 25, 0, 1016, 1101, 0, 375, 1028, 1101, 36, 0,
+    1018, 1101, 0, 21, 1004, 1102, 1, 26, 1009, 1101, 0, 249,
+    1022, 1101, 0, 660, 1025, 1101, 0, 665, 1024, 1102, 1, 22,
+    1011, 1102, 242, 1, 1023, 109, 5, 2102, 1, 3, 63, 1008,
+    63, 31, 63, 1005, 63, 205, 1001, 64, 1, 64, 1105, 1,
+    207, 4, 187, 1002, 64, 2, 64, 109, 8, 21102, 40, 1,
+    5, 1008, 1018, 37, 63, 1005, 63, 227, 1105, 1, 233, 4,
+    213, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 7, 2105,
+    1, 3, 1001, 64, 1, 64, 1106, 0, 251, 4, 239, 1002,
+    64, 2, 64, 109, -7, 1201, -7, 0, 63, 1008, 63, 20,
+    63, 1005, 63, 271, 1106, 0, 277, 4, 257, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, -10, 1208, 0, 33, 63, 1005,
+    63, 295, 4, 283, 1106, 0, 299, 1001, 64, 1, 64, 1002,
+    64, 2, 64, 109, -6, 1207, 4, 27, 63, 1005, 63, 319,
+    1001, 64, 1, 64, 1105, 1, 321, 4, 305, 1002, 64, 2,
+    64, 109, 12, 1207, -1, 33, 63, 1005, 63, 339, 4, 327,
+    1105, 1, 343, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    6, 1206, 6, 355, 1106, 0, 361, 4, 349, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, 21, 2106, 0, -8, 4, 367,
+    1106, 0, 379, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    -29, 1202, 0, 1, 63, 1008, 63, 36, 63, 1005, 63, 403,
+    1001, 64, 1, 64, 1105, 1, 405, 4, 385, 1002, 64, 2,
+    64, 109, 11, 21107, 41, 40, -6, 1005, 1012, 421, 1105, 1,
+    427, 4, 411, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    -11, 2101, 0, -4, 63, 1008, 63, 33, 63, 1005, 63, 453,
+    4, 433, 1001, 64, 1, 64, 1106, 0, 453, 1002, 64, 2,
+    64, 109, -7, 21108, 42, 40, 10, 1005, 1010, 469, 1105, 1,
+    475, 4, 459, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    1, 1201, 4, 0, 63, 1008, 63, 20, 63, 1005, 63, 497,
+    4, 481, 1105, 1, 501, 1001, 64, 1, 64, 1002, 64, 2,
+    64, 109, 5, 21107, 43, 44, 5, 1005, 1011, 523, 4, 507,
+    1001, 64, 1, 64, 1106, 0, 523, 1002, 64, 2, 64, 109,
+    20, 21108, 44, 44, -7, 1005, 1019, 541, 4, 529, 1106, 0,
+    545, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 2, 1205,
+    -8, 561, 1001, 64, 1, 64, 1106, 0, 563, 4, 551, 1002,
+    64, 2, 64, 109, -23, 2108, 22, 0, 63, 1005, 63, 583,
+    1001, 64, 1, 64, 1105, 1, 585, 4, 569, 1002, 64, 2,
+    64, 109, -6, 2107, 30, 1, 63, 1005, 63, 605, 1001, 64,
+    1, 64, 1105, 1, 607, 4, 591, 1002, 64, 2, 64, 109,
+    23, 1205, -1, 621, 4, 613, 1105, 1, 625, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, -19, 2102, 1, -3, 63, 1008,
+    63, 29, 63, 1005, 63, 647, 4, 631, 1106, 0, 651, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, 28, 2105, 1, -7,
+    4, 657, 1106, 0, 669, 1001, 64, 1, 64, 1002, 64, 2,
+    64, 109, -17, 1206, 6, 687, 4, 675, 1001, 64, 1, 64,
+    1105, 1, 687, 1002, 64, 2, 64, 109, 2, 21101, 45, 0,
+    1, 1008, 1017, 42, 63, 1005, 63, 707, 1106, 0, 713, 4,
+    693, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -6, 2101,
+    0, -3, 63, 1008, 63, 34, 63, 1005, 63, 733, 1105, 1,
+    739, 4, 719, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    3, 21101, 46, 0, 1, 1008, 1014, 46, 63, 1005, 63, 761,
+    4, 745, 1106, 0, 765, 1001, 64, 1, 64, 1002, 64, 2,
+    64, 109, 5, 21102, 47, 1, -7, 1008, 1011, 47, 63, 1005,
+    63, 787, 4, 771, 1105, 1, 791, 1001, 64, 1, 64, 1002,
+    64, 2, 64, 109, -24, 2108, 24, 8, 63, 1005, 63, 813,
+    4, 797, 1001, 64, 1, 64, 1106, 0, 813, 1002, 64, 2,
+    64, 109, 5, 1208, 10, 29, 63, 1005, 63, 829, 1105, 1,
+    835, 4, 819, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    7, 2107, 23, -4, 63, 1005, 63, 853, 4, 841, 1105, 1,
+    857, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -2, 1202,
+    0, 1, 63, 1008, 63, 21, 63, 1005, 63, 879, 4, 863,
+    1105, 1, 883, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    15, 2106, 0, 8, 1106, 0, 901, 4, 889, 1001, 64, 1,
+    64, 4, 64, 99, 21102, 1, 27, 1, 21102, 915, 1, 0,
+    1105, 1, 922, 21201, 1, 51839, 1, 204, 1, 99, 109, 3,
+    1207, -2, 3, 63, 1005, 63, 964, 21201, -2, -1, 1, 21101,
+    942, 0, 0, 1106, 0, 922, 21201, 1, 0, -1, 21201, -2,
+    -3, 1, 21101, 957, 0, 0, 1105, 1, 922, 22201, 1, -1,
+    -2, 1105, 1, 968, 21201, -2, 0, -2, 109, -3, 2106, 0,
+    0,
+];
+
+/// Address ranges of the translated instructions.
+const CODE: &[(Address, Address)] = &[
+    (0, 63), (65, 973),
+];
+
+/// Writes past this address are left to the interpreter, which allocates memory sparsely.
+const DENSE_MEMORY: Address = 1 << 20;
+
+/// Leaves the instruction at `$address` to the interpreter if `$operand` is `None`.
+macro_rules! or_interpret {
+    ($machine:ident, $address:expr, $operand:expr) => {
+        match $operand {
+            Some(operand) => operand,
+            None => {
+                $machine.instruction_pointer = $address;
+                return $machine.fall_back();
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+pub struct Machine {
+    memory: Vec<Word>,
+    code: Vec<bool>,
+    instruction_pointer: Address,
+    relative_base_offset: Word,
+    input: VecDeque<Word>,
+    halted: bool,
+    interpreter: Option<Emulator<Word>>,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        let mut code = vec![false; PROGRAM.len()];
+        for &(start, end) in CODE {
+            code[start..end].iter_mut().for_each(|word| *word = true);
+        }
+        Self {
+            memory: PROGRAM.to_vec(),
+            code,
+            instruction_pointer: 0,
+            relative_base_offset: 0,
+            input: VecDeque::new(),
+            halted: false,
+            interpreter: None,
+        }
+    }
+
+    pub fn push_input(&mut self, input: Word) {
+        match &mut self.interpreter {
+            Some(emulator) => emulator.push_input(input),
+            None => self.input.push_back(input),
+        }
+    }
+
+    /// Whether the program left the translated code and runs on the interpreter now.
+    pub fn is_interpreted(&self) -> bool {
+        self.interpreter.is_some()
+    }
+
+    /// Feeds `inputs` to the program and runs it until it halts, like
+    /// `Emulator::run_to_completion`.
+    pub fn run_to_completion(
+        &mut self,
+        inputs: impl IntoIterator<Item = Word>,
+    ) -> Result<Vec<Word>, EmulatorError<Word>> {
+        inputs.into_iter().for_each(|input| self.push_input(input));
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunResult::Output(output) => outputs.push(output),
+                RunResult::Halt => return Ok(outputs),
+                _ => {
+                    let instruction_pointer = match &self.interpreter {
+                        // the interpreter's input instruction has already moved past itself
+                        Some(emulator) => emulator.instruction_pointer() - 2,
+                        None => self.instruction_pointer,
+                    };
+                    return Err(EmulatorError::InputExhausted { instruction_pointer });
+                }
+            }
+        }
+    }
+
+    fn read(&self, address: Address) -> Word {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    fn relative(&self, offset: Word) -> Option<Address> {
+        Address::try_from(self.relative_base_offset + offset).ok()
+    }
+
+    fn writable(&self, address: Address) -> Option<Address> {
+        let code = self.code.get(address).copied().unwrap_or(false);
+        Some(address).filter(|&address| address < DENSE_MEMORY && !code)
+    }
+
+    fn store(&mut self, address: Address, value: Word) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    fn fall_back(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        let memory = std::mem::take(&mut self.memory);
+        let mut emulator = Emulator::resume(
+            memory,
+            self.instruction_pointer,
+            self.relative_base_offset,
+        );
+        emulator.extend_input(self.input.drain(..));
+        self.interpreter.get_or_insert(emulator).run()
+    }
+
+    pub fn run(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        if let Some(emulator) = &mut self.interpreter {
+            return emulator.run();
+        }
+        if self.halted {
+            return Ok(RunResult::Halt);
+        }
+        loop {
+            match self.instruction_pointer {
+                0 => {
+                    // 0: mul #34463338, #34463338, [63]
+                    let value = (34463338 as Word) * (34463338 as Word);
+                    let target = or_interpret!(self, 0, self.writable(63));
+                    self.store(target, value);
+                    // 4: less_than [63], #34463338, [63]
+                    let value = (self.read(63) < (34463338 as Word)) as Word;
+                    let target = or_interpret!(self, 4, self.writable(63));
+                    self.store(target, value);
+                    // 8: jump_if_true [63], #53
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 8, Address::try_from((53 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 11;
+                }
+                11 => {
+                    // 11: add #3, #0, [1000]
+                    let value = (3 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 11, self.writable(1000));
+                    self.store(target, value);
+                    // 15: add_to_relative_base #988
+                    self.relative_base_offset += (988 as Word);
+                    // 17: add_to_relative_base rb+12
+                    self.relative_base_offset += self.read(or_interpret!(self, 17, self.relative(12)));
+                    // 19: add_to_relative_base [1000]
+                    self.relative_base_offset += self.read(1000);
+                    // 21: add_to_relative_base rb+6
+                    self.relative_base_offset += self.read(or_interpret!(self, 21, self.relative(6)));
+                    // 23: add_to_relative_base rb+3
+                    self.relative_base_offset += self.read(or_interpret!(self, 23, self.relative(3)));
+                    self.instruction_pointer = 25;
+                }
+                25 => {
+                    // 25: input rb+0
+                    let target = or_interpret!(self, 25, self.relative(0).and_then(|target| self.writable(target)));
+                    match self.input.pop_front() {
+                        Some(input) => self.store(target, input),
+                        None => {
+                            self.instruction_pointer = 25;
+                            return Ok(RunResult::InputRequest);
+                        }
+                    }
+                    // 27: equals [1000], #1, [63]
+                    let value = (self.read(1000) == (1 as Word)) as Word;
+                    let target = or_interpret!(self, 27, self.writable(63));
+                    self.store(target, value);
+                    // 31: jump_if_true [63], #65
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 31, Address::try_from((65 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 34;
+                }
+                34 => {
+                    // 34: equals [1000], #2, [63]
+                    let value = (self.read(1000) == (2 as Word)) as Word;
+                    let target = or_interpret!(self, 34, self.writable(63));
+                    self.store(target, value);
+                    // 38: jump_if_true [63], #904
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 38, Address::try_from((904 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 41;
+                }
+                41 => {
+                    // 41: equals [1000], #0, [63]
+                    let value = (self.read(1000) == (0 as Word)) as Word;
+                    let target = or_interpret!(self, 41, self.writable(63));
+                    self.store(target, value);
+                    // 45: jump_if_true [63], #58
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 45, Address::try_from((58 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 48;
+                }
+                48 => {
+                    // 48: output [25]
+                    let value = self.read(25);
+                    self.instruction_pointer = 50;
+                    return Ok(RunResult::Output(value));
+                }
+                50 => {
+                    // 50: output #0
+                    let value = (0 as Word);
+                    self.instruction_pointer = 52;
+                    return Ok(RunResult::Output(value));
+                }
+                52 => {
+                    // 52: halt
+                    self.instruction_pointer = 52;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                53 => {
+                    // 53: output [0]
+                    let value = self.read(0);
+                    self.instruction_pointer = 55;
+                    return Ok(RunResult::Output(value));
+                }
+                55 => {
+                    // 55: output #0
+                    let value = (0 as Word);
+                    self.instruction_pointer = 57;
+                    return Ok(RunResult::Output(value));
+                }
+                57 => {
+                    // 57: halt
+                    self.instruction_pointer = 57;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                58 => {
+                    // 58: output [17]
+                    let value = self.read(17);
+                    self.instruction_pointer = 60;
+                    return Ok(RunResult::Output(value));
+                }
+                60 => {
+                    // 60: output #0
+                    let value = (0 as Word);
+                    self.instruction_pointer = 62;
+                    return Ok(RunResult::Output(value));
+                }
+                62 => {
+                    // 62: halt
+                    self.instruction_pointer = 62;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                65 => {
+                    // 65: add #35, #0, [1007]
+                    let value = (35 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 65, self.writable(1007));
+                    self.store(target, value);
+                    // 69: mul #30, #1, [1013]
+                    let value = (30 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 69, self.writable(1013));
+                    self.store(target, value);
+                    // 73: mul #37, #1, [1017]
+                    let value = (37 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 73, self.writable(1017));
+                    self.store(target, value);
+                    // 77: add #23, #0, [1006]
+                    let value = (23 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 77, self.writable(1006));
+                    self.store(target, value);
+                    // 81: add #0, #32, [1008]
+                    let value = (0 as Word) + (32 as Word);
+                    let target = or_interpret!(self, 81, self.writable(1008));
+                    self.store(target, value);
+                    // 85: mul #1, #29, [1000]
+                    let value = (1 as Word) * (29 as Word);
+                    let target = or_interpret!(self, 85, self.writable(1000));
+                    self.store(target, value);
+                    // 89: add #0, #38, [1010]
+                    let value = (0 as Word) + (38 as Word);
+                    let target = or_interpret!(self, 89, self.writable(1010));
+                    self.store(target, value);
+                    // 93: add #0, #24, [1002]
+                    let value = (0 as Word) + (24 as Word);
+                    let target = or_interpret!(self, 93, self.writable(1002));
+                    self.store(target, value);
+                    // 97: add #33, #0, [1003]
+                    let value = (33 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 97, self.writable(1003));
+                    self.store(target, value);
+                    // 101: add #1, #0, [1021]
+                    let value = (1 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 101, self.writable(1021));
+                    self.store(target, value);
+                    // 105: mul #31, #1, [1019]
+                    let value = (31 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 105, self.writable(1019));
+                    self.store(target, value);
+                    // 109: add #27, #0, [1014]
+                    let value = (27 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 109, self.writable(1014));
+                    self.store(target, value);
+                    // 113: mul #20, #1, [1005]
+                    let value = (20 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 113, self.writable(1005));
+                    self.store(target, value);
+                    // 117: add #0, #0, [1020]
+                    let value = (0 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 117, self.writable(1020));
+                    self.store(target, value);
+                    // 121: mul #1, #892, [1027]
+                    let value = (1 as Word) * (892 as Word);
+                    let target = or_interpret!(self, 121, self.writable(1027));
+                    self.store(target, value);
+                    // 125: add #895, #0, [1026]
+                    let value = (895 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 125, self.writable(1026));
+                    self.store(target, value);
+                    // 129: mul #39, #1, [1015]
+                    let value = (39 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 129, self.writable(1015));
+                    self.store(target, value);
+                    // 133: mul #1, #370, [1029]
+                    let value = (1 as Word) * (370 as Word);
+                    let target = or_interpret!(self, 133, self.writable(1029));
+                    self.store(target, value);
+                    // 137: mul #1, #28, [1001]
+                    let value = (1 as Word) * (28 as Word);
+                    let target = or_interpret!(self, 137, self.writable(1001));
+                    self.store(target, value);
+                    // 141: mul #34, #1, [1012]
+                    let value = (34 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 141, self.writable(1012));
+                    self.store(target, value);
+                    // 145: add #25, #0, [1016]
+                    let value = (25 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 145, self.writable(1016));
+                    self.store(target, value);
+                    // 149: add #0, #375, [1028]
+                    let value = (0 as Word) + (375 as Word);
+                    let target = or_interpret!(self, 149, self.writable(1028));
+                    self.store(target, value);
+                    // 153: add #36, #0, [1018]
+                    let value = (36 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 153, self.writable(1018));
+                    self.store(target, value);
+                    // 157: add #0, #21, [1004]
+                    let value = (0 as Word) + (21 as Word);
+                    let target = or_interpret!(self, 157, self.writable(1004));
+                    self.store(target, value);
+                    // 161: mul #1, #26, [1009]
+                    let value = (1 as Word) * (26 as Word);
+                    let target = or_interpret!(self, 161, self.writable(1009));
+                    self.store(target, value);
+                    // 165: add #0, #249, [1022]
+                    let value = (0 as Word) + (249 as Word);
+                    let target = or_interpret!(self, 165, self.writable(1022));
+                    self.store(target, value);
+                    // 169: add #0, #660, [1025]
+                    let value = (0 as Word) + (660 as Word);
+                    let target = or_interpret!(self, 169, self.writable(1025));
+                    self.store(target, value);
+                    // 173: add #0, #665, [1024]
+                    let value = (0 as Word) + (665 as Word);
+                    let target = or_interpret!(self, 173, self.writable(1024));
+                    self.store(target, value);
+                    // 177: mul #1, #22, [1011]
+                    let value = (1 as Word) * (22 as Word);
+                    let target = or_interpret!(self, 177, self.writable(1011));
+                    self.store(target, value);
+                    // 181: mul #242, #1, [1023]
+                    let value = (242 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 181, self.writable(1023));
+                    self.store(target, value);
+                    // 185: add_to_relative_base #5
+                    self.relative_base_offset += (5 as Word);
+                    // 187: mul #1, rb+3, [63]
+                    let value = (1 as Word) * self.read(or_interpret!(self, 187, self.relative(3)));
+                    let target = or_interpret!(self, 187, self.writable(63));
+                    self.store(target, value);
+                    // 191: equals [63], #31, [63]
+                    let value = (self.read(63) == (31 as Word)) as Word;
+                    let target = or_interpret!(self, 191, self.writable(63));
+                    self.store(target, value);
+                    // 195: jump_if_true [63], #205
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 195, Address::try_from((205 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 198;
+                }
+                198 => {
+                    // 198: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 198, self.writable(64));
+                    self.store(target, value);
+                    // 202: jump_if_true #1, #207
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 202, Address::try_from((207 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 205;
+                }
+                205 => {
+                    // 205: output [187]
+                    let value = self.read(187);
+                    self.instruction_pointer = 207;
+                    return Ok(RunResult::Output(value));
+                }
+                207 => {
+                    // 207: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 207, self.writable(64));
+                    self.store(target, value);
+                    // 211: add_to_relative_base #8
+                    self.relative_base_offset += (8 as Word);
+                    // 213: mul #40, #1, rb+5
+                    let value = (40 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 213, self.relative(5).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 217: equals [1018], #37, [63]
+                    let value = (self.read(1018) == (37 as Word)) as Word;
+                    let target = or_interpret!(self, 217, self.writable(63));
+                    self.store(target, value);
+                    // 221: jump_if_true [63], #227
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 221, Address::try_from((227 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 224;
+                }
+                224 => {
+                    // 224: jump_if_true #1, #233
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 224, Address::try_from((233 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 227;
+                }
+                227 => {
+                    // 227: output [213]
+                    let value = self.read(213);
+                    self.instruction_pointer = 229;
+                    return Ok(RunResult::Output(value));
+                }
+                229 => {
+                    // 229: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 229, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 233;
+                }
+                233 => {
+                    // 233: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 233, self.writable(64));
+                    self.store(target, value);
+                    // 237: add_to_relative_base #7
+                    self.relative_base_offset += (7 as Word);
+                    // 239: jump_if_true #1, rb+3
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 239, Address::try_from(self.read(or_interpret!(self, 239, self.relative(3)))).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 242;
+                }
+                242 => {
+                    // 242: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 242, self.writable(64));
+                    self.store(target, value);
+                    // 246: jump_if_false #0, #251
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 246, Address::try_from((251 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 249;
+                }
+                249 => {
+                    // 249: output [239]
+                    let value = self.read(239);
+                    self.instruction_pointer = 251;
+                    return Ok(RunResult::Output(value));
+                }
+                251 => {
+                    // 251: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 251, self.writable(64));
+                    self.store(target, value);
+                    // 255: add_to_relative_base #-7
+                    self.relative_base_offset += (-7 as Word);
+                    // 257: add rb-7, #0, [63]
+                    let value = self.read(or_interpret!(self, 257, self.relative(-7))) + (0 as Word);
+                    let target = or_interpret!(self, 257, self.writable(63));
+                    self.store(target, value);
+                    // 261: equals [63], #20, [63]
+                    let value = (self.read(63) == (20 as Word)) as Word;
+                    let target = or_interpret!(self, 261, self.writable(63));
+                    self.store(target, value);
+                    // 265: jump_if_true [63], #271
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 265, Address::try_from((271 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 268;
+                }
+                268 => {
+                    // 268: jump_if_false #0, #277
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 268, Address::try_from((277 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 271;
+                }
+                271 => {
+                    // 271: output [257]
+                    let value = self.read(257);
+                    self.instruction_pointer = 273;
+                    return Ok(RunResult::Output(value));
+                }
+                273 => {
+                    // 273: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 273, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 277;
+                }
+                277 => {
+                    // 277: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 277, self.writable(64));
+                    self.store(target, value);
+                    // 281: add_to_relative_base #-10
+                    self.relative_base_offset += (-10 as Word);
+                    // 283: equals rb+0, #33, [63]
+                    let value = (self.read(or_interpret!(self, 283, self.relative(0))) == (33 as Word)) as Word;
+                    let target = or_interpret!(self, 283, self.writable(63));
+                    self.store(target, value);
+                    // 287: jump_if_true [63], #295
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 287, Address::try_from((295 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 290;
+                }
+                290 => {
+                    // 290: output [283]
+                    let value = self.read(283);
+                    self.instruction_pointer = 292;
+                    return Ok(RunResult::Output(value));
+                }
+                292 => {
+                    // 292: jump_if_false #0, #299
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 292, Address::try_from((299 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 295;
+                }
+                295 => {
+                    // 295: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 295, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 299;
+                }
+                299 => {
+                    // 299: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 299, self.writable(64));
+                    self.store(target, value);
+                    // 303: add_to_relative_base #-6
+                    self.relative_base_offset += (-6 as Word);
+                    // 305: less_than rb+4, #27, [63]
+                    let value = (self.read(or_interpret!(self, 305, self.relative(4))) < (27 as Word)) as Word;
+                    let target = or_interpret!(self, 305, self.writable(63));
+                    self.store(target, value);
+                    // 309: jump_if_true [63], #319
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 309, Address::try_from((319 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 312;
+                }
+                312 => {
+                    // 312: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 312, self.writable(64));
+                    self.store(target, value);
+                    // 316: jump_if_true #1, #321
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 316, Address::try_from((321 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 319;
+                }
+                319 => {
+                    // 319: output [305]
+                    let value = self.read(305);
+                    self.instruction_pointer = 321;
+                    return Ok(RunResult::Output(value));
+                }
+                321 => {
+                    // 321: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 321, self.writable(64));
+                    self.store(target, value);
+                    // 325: add_to_relative_base #12
+                    self.relative_base_offset += (12 as Word);
+                    // 327: less_than rb-1, #33, [63]
+                    let value = (self.read(or_interpret!(self, 327, self.relative(-1))) < (33 as Word)) as Word;
+                    let target = or_interpret!(self, 327, self.writable(63));
+                    self.store(target, value);
+                    // 331: jump_if_true [63], #339
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 331, Address::try_from((339 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 334;
+                }
+                334 => {
+                    // 334: output [327]
+                    let value = self.read(327);
+                    self.instruction_pointer = 336;
+                    return Ok(RunResult::Output(value));
+                }
+                336 => {
+                    // 336: jump_if_true #1, #343
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 336, Address::try_from((343 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 339;
+                }
+                339 => {
+                    // 339: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 339, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 343;
+                }
+                343 => {
+                    // 343: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 343, self.writable(64));
+                    self.store(target, value);
+                    // 347: add_to_relative_base #6
+                    self.relative_base_offset += (6 as Word);
+                    // 349: jump_if_false rb+6, #355
+                    if self.read(or_interpret!(self, 349, self.relative(6))) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 349, Address::try_from((355 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 352;
+                }
+                352 => {
+                    // 352: jump_if_false #0, #361
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 352, Address::try_from((361 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 355;
+                }
+                355 => {
+                    // 355: output [349]
+                    let value = self.read(349);
+                    self.instruction_pointer = 357;
+                    return Ok(RunResult::Output(value));
+                }
+                357 => {
+                    // 357: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 357, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 361;
+                }
+                361 => {
+                    // 361: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 361, self.writable(64));
+                    self.store(target, value);
+                    // 365: add_to_relative_base #21
+                    self.relative_base_offset += (21 as Word);
+                    // 367: jump_if_false #0, rb-8
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 367, Address::try_from(self.read(or_interpret!(self, 367, self.relative(-8)))).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 370;
+                }
+                370 => {
+                    // 370: output [367]
+                    let value = self.read(367);
+                    self.instruction_pointer = 372;
+                    return Ok(RunResult::Output(value));
+                }
+                372 => {
+                    // 372: jump_if_false #0, #379
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 372, Address::try_from((379 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 375;
+                }
+                375 => {
+                    // 375: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 375, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 379;
+                }
+                379 => {
+                    // 379: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 379, self.writable(64));
+                    self.store(target, value);
+                    // 383: add_to_relative_base #-29
+                    self.relative_base_offset += (-29 as Word);
+                    // 385: mul rb+0, #1, [63]
+                    let value = self.read(or_interpret!(self, 385, self.relative(0))) * (1 as Word);
+                    let target = or_interpret!(self, 385, self.writable(63));
+                    self.store(target, value);
+                    // 389: equals [63], #36, [63]
+                    let value = (self.read(63) == (36 as Word)) as Word;
+                    let target = or_interpret!(self, 389, self.writable(63));
+                    self.store(target, value);
+                    // 393: jump_if_true [63], #403
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 393, Address::try_from((403 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 396;
+                }
+                396 => {
+                    // 396: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 396, self.writable(64));
+                    self.store(target, value);
+                    // 400: jump_if_true #1, #405
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 400, Address::try_from((405 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 403;
+                }
+                403 => {
+                    // 403: output [385]
+                    let value = self.read(385);
+                    self.instruction_pointer = 405;
+                    return Ok(RunResult::Output(value));
+                }
+                405 => {
+                    // 405: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 405, self.writable(64));
+                    self.store(target, value);
+                    // 409: add_to_relative_base #11
+                    self.relative_base_offset += (11 as Word);
+                    // 411: less_than #41, #40, rb-6
+                    let value = ((41 as Word) < (40 as Word)) as Word;
+                    let target = or_interpret!(self, 411, self.relative(-6).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 415: jump_if_true [1012], #421
+                    if self.read(1012) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 415, Address::try_from((421 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 418;
+                }
+                418 => {
+                    // 418: jump_if_true #1, #427
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 418, Address::try_from((427 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 421;
+                }
+                421 => {
+                    // 421: output [411]
+                    let value = self.read(411);
+                    self.instruction_pointer = 423;
+                    return Ok(RunResult::Output(value));
+                }
+                423 => {
+                    // 423: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 423, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 427;
+                }
+                427 => {
+                    // 427: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 427, self.writable(64));
+                    self.store(target, value);
+                    // 431: add_to_relative_base #-11
+                    self.relative_base_offset += (-11 as Word);
+                    // 433: add #0, rb-4, [63]
+                    let value = (0 as Word) + self.read(or_interpret!(self, 433, self.relative(-4)));
+                    let target = or_interpret!(self, 433, self.writable(63));
+                    self.store(target, value);
+                    // 437: equals [63], #33, [63]
+                    let value = (self.read(63) == (33 as Word)) as Word;
+                    let target = or_interpret!(self, 437, self.writable(63));
+                    self.store(target, value);
+                    // 441: jump_if_true [63], #453
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 441, Address::try_from((453 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 444;
+                }
+                444 => {
+                    // 444: output [433]
+                    let value = self.read(433);
+                    self.instruction_pointer = 446;
+                    return Ok(RunResult::Output(value));
+                }
+                446 => {
+                    // 446: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 446, self.writable(64));
+                    self.store(target, value);
+                    // 450: jump_if_false #0, #453
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 450, Address::try_from((453 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 453;
+                }
+                453 => {
+                    // 453: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 453, self.writable(64));
+                    self.store(target, value);
+                    // 457: add_to_relative_base #-7
+                    self.relative_base_offset += (-7 as Word);
+                    // 459: equals #42, #40, rb+10
+                    let value = ((42 as Word) == (40 as Word)) as Word;
+                    let target = or_interpret!(self, 459, self.relative(10).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 463: jump_if_true [1010], #469
+                    if self.read(1010) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 463, Address::try_from((469 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 466;
+                }
+                466 => {
+                    // 466: jump_if_true #1, #475
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 466, Address::try_from((475 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 469;
+                }
+                469 => {
+                    // 469: output [459]
+                    let value = self.read(459);
+                    self.instruction_pointer = 471;
+                    return Ok(RunResult::Output(value));
+                }
+                471 => {
+                    // 471: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 471, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 475;
+                }
+                475 => {
+                    // 475: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 475, self.writable(64));
+                    self.store(target, value);
+                    // 479: add_to_relative_base #1
+                    self.relative_base_offset += (1 as Word);
+                    // 481: add rb+4, #0, [63]
+                    let value = self.read(or_interpret!(self, 481, self.relative(4))) + (0 as Word);
+                    let target = or_interpret!(self, 481, self.writable(63));
+                    self.store(target, value);
+                    // 485: equals [63], #20, [63]
+                    let value = (self.read(63) == (20 as Word)) as Word;
+                    let target = or_interpret!(self, 485, self.writable(63));
+                    self.store(target, value);
+                    // 489: jump_if_true [63], #497
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 489, Address::try_from((497 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 492;
+                }
+                492 => {
+                    // 492: output [481]
+                    let value = self.read(481);
+                    self.instruction_pointer = 494;
+                    return Ok(RunResult::Output(value));
+                }
+                494 => {
+                    // 494: jump_if_true #1, #501
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 494, Address::try_from((501 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 497;
+                }
+                497 => {
+                    // 497: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 497, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 501;
+                }
+                501 => {
+                    // 501: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 501, self.writable(64));
+                    self.store(target, value);
+                    // 505: add_to_relative_base #5
+                    self.relative_base_offset += (5 as Word);
+                    // 507: less_than #43, #44, rb+5
+                    let value = ((43 as Word) < (44 as Word)) as Word;
+                    let target = or_interpret!(self, 507, self.relative(5).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 511: jump_if_true [1011], #523
+                    if self.read(1011) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 511, Address::try_from((523 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 514;
+                }
+                514 => {
+                    // 514: output [507]
+                    let value = self.read(507);
+                    self.instruction_pointer = 516;
+                    return Ok(RunResult::Output(value));
+                }
+                516 => {
+                    // 516: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 516, self.writable(64));
+                    self.store(target, value);
+                    // 520: jump_if_false #0, #523
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 520, Address::try_from((523 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 523;
+                }
+                523 => {
+                    // 523: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 523, self.writable(64));
+                    self.store(target, value);
+                    // 527: add_to_relative_base #20
+                    self.relative_base_offset += (20 as Word);
+                    // 529: equals #44, #44, rb-7
+                    let value = ((44 as Word) == (44 as Word)) as Word;
+                    let target = or_interpret!(self, 529, self.relative(-7).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 533: jump_if_true [1019], #541
+                    if self.read(1019) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 533, Address::try_from((541 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 536;
+                }
+                536 => {
+                    // 536: output [529]
+                    let value = self.read(529);
+                    self.instruction_pointer = 538;
+                    return Ok(RunResult::Output(value));
+                }
+                538 => {
+                    // 538: jump_if_false #0, #545
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 538, Address::try_from((545 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 541;
+                }
+                541 => {
+                    // 541: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 541, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 545;
+                }
+                545 => {
+                    // 545: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 545, self.writable(64));
+                    self.store(target, value);
+                    // 549: add_to_relative_base #2
+                    self.relative_base_offset += (2 as Word);
+                    // 551: jump_if_true rb-8, #561
+                    if self.read(or_interpret!(self, 551, self.relative(-8))) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 551, Address::try_from((561 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 554;
+                }
+                554 => {
+                    // 554: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 554, self.writable(64));
+                    self.store(target, value);
+                    // 558: jump_if_false #0, #563
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 558, Address::try_from((563 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 561;
+                }
+                561 => {
+                    // 561: output [551]
+                    let value = self.read(551);
+                    self.instruction_pointer = 563;
+                    return Ok(RunResult::Output(value));
+                }
+                563 => {
+                    // 563: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 563, self.writable(64));
+                    self.store(target, value);
+                    // 567: add_to_relative_base #-23
+                    self.relative_base_offset += (-23 as Word);
+                    // 569: equals #22, rb+0, [63]
+                    let value = ((22 as Word) == self.read(or_interpret!(self, 569, self.relative(0)))) as Word;
+                    let target = or_interpret!(self, 569, self.writable(63));
+                    self.store(target, value);
+                    // 573: jump_if_true [63], #583
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 573, Address::try_from((583 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 576;
+                }
+                576 => {
+                    // 576: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 576, self.writable(64));
+                    self.store(target, value);
+                    // 580: jump_if_true #1, #585
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 580, Address::try_from((585 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 583;
+                }
+                583 => {
+                    // 583: output [569]
+                    let value = self.read(569);
+                    self.instruction_pointer = 585;
+                    return Ok(RunResult::Output(value));
+                }
+                585 => {
+                    // 585: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 585, self.writable(64));
+                    self.store(target, value);
+                    // 589: add_to_relative_base #-6
+                    self.relative_base_offset += (-6 as Word);
+                    // 591: less_than #30, rb+1, [63]
+                    let value = ((30 as Word) < self.read(or_interpret!(self, 591, self.relative(1)))) as Word;
+                    let target = or_interpret!(self, 591, self.writable(63));
+                    self.store(target, value);
+                    // 595: jump_if_true [63], #605
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 595, Address::try_from((605 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 598;
+                }
+                598 => {
+                    // 598: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 598, self.writable(64));
+                    self.store(target, value);
+                    // 602: jump_if_true #1, #607
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 602, Address::try_from((607 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 605;
+                }
+                605 => {
+                    // 605: output [591]
+                    let value = self.read(591);
+                    self.instruction_pointer = 607;
+                    return Ok(RunResult::Output(value));
+                }
+                607 => {
+                    // 607: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 607, self.writable(64));
+                    self.store(target, value);
+                    // 611: add_to_relative_base #23
+                    self.relative_base_offset += (23 as Word);
+                    // 613: jump_if_true rb-1, #621
+                    if self.read(or_interpret!(self, 613, self.relative(-1))) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 613, Address::try_from((621 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 616;
+                }
+                616 => {
+                    // 616: output [613]
+                    let value = self.read(613);
+                    self.instruction_pointer = 618;
+                    return Ok(RunResult::Output(value));
+                }
+                618 => {
+                    // 618: jump_if_true #1, #625
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 618, Address::try_from((625 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 621;
+                }
+                621 => {
+                    // 621: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 621, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 625;
+                }
+                625 => {
+                    // 625: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 625, self.writable(64));
+                    self.store(target, value);
+                    // 629: add_to_relative_base #-19
+                    self.relative_base_offset += (-19 as Word);
+                    // 631: mul #1, rb-3, [63]
+                    let value = (1 as Word) * self.read(or_interpret!(self, 631, self.relative(-3)));
+                    let target = or_interpret!(self, 631, self.writable(63));
+                    self.store(target, value);
+                    // 635: equals [63], #29, [63]
+                    let value = (self.read(63) == (29 as Word)) as Word;
+                    let target = or_interpret!(self, 635, self.writable(63));
+                    self.store(target, value);
+                    // 639: jump_if_true [63], #647
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 639, Address::try_from((647 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 642;
+                }
+                642 => {
+                    // 642: output [631]
+                    let value = self.read(631);
+                    self.instruction_pointer = 644;
+                    return Ok(RunResult::Output(value));
+                }
+                644 => {
+                    // 644: jump_if_false #0, #651
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 644, Address::try_from((651 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 647;
+                }
+                647 => {
+                    // 647: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 647, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 651;
+                }
+                651 => {
+                    // 651: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 651, self.writable(64));
+                    self.store(target, value);
+                    // 655: add_to_relative_base #28
+                    self.relative_base_offset += (28 as Word);
+                    // 657: jump_if_true #1, rb-7
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 657, Address::try_from(self.read(or_interpret!(self, 657, self.relative(-7)))).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 660;
+                }
+                660 => {
+                    // 660: output [657]
+                    let value = self.read(657);
+                    self.instruction_pointer = 662;
+                    return Ok(RunResult::Output(value));
+                }
+                662 => {
+                    // 662: jump_if_false #0, #669
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 662, Address::try_from((669 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 665;
+                }
+                665 => {
+                    // 665: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 665, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 669;
+                }
+                669 => {
+                    // 669: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 669, self.writable(64));
+                    self.store(target, value);
+                    // 673: add_to_relative_base #-17
+                    self.relative_base_offset += (-17 as Word);
+                    // 675: jump_if_false rb+6, #687
+                    if self.read(or_interpret!(self, 675, self.relative(6))) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 675, Address::try_from((687 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 678;
+                }
+                678 => {
+                    // 678: output [675]
+                    let value = self.read(675);
+                    self.instruction_pointer = 680;
+                    return Ok(RunResult::Output(value));
+                }
+                680 => {
+                    // 680: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 680, self.writable(64));
+                    self.store(target, value);
+                    // 684: jump_if_true #1, #687
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 684, Address::try_from((687 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 687;
+                }
+                687 => {
+                    // 687: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 687, self.writable(64));
+                    self.store(target, value);
+                    // 691: add_to_relative_base #2
+                    self.relative_base_offset += (2 as Word);
+                    // 693: add #45, #0, rb+1
+                    let value = (45 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 693, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 697: equals [1017], #42, [63]
+                    let value = (self.read(1017) == (42 as Word)) as Word;
+                    let target = or_interpret!(self, 697, self.writable(63));
+                    self.store(target, value);
+                    // 701: jump_if_true [63], #707
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 701, Address::try_from((707 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 704;
+                }
+                704 => {
+                    // 704: jump_if_false #0, #713
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 704, Address::try_from((713 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 707;
+                }
+                707 => {
+                    // 707: output [693]
+                    let value = self.read(693);
+                    self.instruction_pointer = 709;
+                    return Ok(RunResult::Output(value));
+                }
+                709 => {
+                    // 709: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 709, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 713;
+                }
+                713 => {
+                    // 713: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 713, self.writable(64));
+                    self.store(target, value);
+                    // 717: add_to_relative_base #-6
+                    self.relative_base_offset += (-6 as Word);
+                    // 719: add #0, rb-3, [63]
+                    let value = (0 as Word) + self.read(or_interpret!(self, 719, self.relative(-3)));
+                    let target = or_interpret!(self, 719, self.writable(63));
+                    self.store(target, value);
+                    // 723: equals [63], #34, [63]
+                    let value = (self.read(63) == (34 as Word)) as Word;
+                    let target = or_interpret!(self, 723, self.writable(63));
+                    self.store(target, value);
+                    // 727: jump_if_true [63], #733
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 727, Address::try_from((733 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 730;
+                }
+                730 => {
+                    // 730: jump_if_true #1, #739
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 730, Address::try_from((739 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 733;
+                }
+                733 => {
+                    // 733: output [719]
+                    let value = self.read(719);
+                    self.instruction_pointer = 735;
+                    return Ok(RunResult::Output(value));
+                }
+                735 => {
+                    // 735: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 735, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 739;
+                }
+                739 => {
+                    // 739: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 739, self.writable(64));
+                    self.store(target, value);
+                    // 743: add_to_relative_base #3
+                    self.relative_base_offset += (3 as Word);
+                    // 745: add #46, #0, rb+1
+                    let value = (46 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 745, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 749: equals [1014], #46, [63]
+                    let value = (self.read(1014) == (46 as Word)) as Word;
+                    let target = or_interpret!(self, 749, self.writable(63));
+                    self.store(target, value);
+                    // 753: jump_if_true [63], #761
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 753, Address::try_from((761 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 756;
+                }
+                756 => {
+                    // 756: output [745]
+                    let value = self.read(745);
+                    self.instruction_pointer = 758;
+                    return Ok(RunResult::Output(value));
+                }
+                758 => {
+                    // 758: jump_if_false #0, #765
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 758, Address::try_from((765 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 761;
+                }
+                761 => {
+                    // 761: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 761, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 765;
+                }
+                765 => {
+                    // 765: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 765, self.writable(64));
+                    self.store(target, value);
+                    // 769: add_to_relative_base #5
+                    self.relative_base_offset += (5 as Word);
+                    // 771: mul #47, #1, rb-7
+                    let value = (47 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 771, self.relative(-7).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 775: equals [1011], #47, [63]
+                    let value = (self.read(1011) == (47 as Word)) as Word;
+                    let target = or_interpret!(self, 775, self.writable(63));
+                    self.store(target, value);
+                    // 779: jump_if_true [63], #787
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 779, Address::try_from((787 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 782;
+                }
+                782 => {
+                    // 782: output [771]
+                    let value = self.read(771);
+                    self.instruction_pointer = 784;
+                    return Ok(RunResult::Output(value));
+                }
+                784 => {
+                    // 784: jump_if_true #1, #791
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 784, Address::try_from((791 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 787;
+                }
+                787 => {
+                    // 787: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 787, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 791;
+                }
+                791 => {
+                    // 791: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 791, self.writable(64));
+                    self.store(target, value);
+                    // 795: add_to_relative_base #-24
+                    self.relative_base_offset += (-24 as Word);
+                    // 797: equals #24, rb+8, [63]
+                    let value = ((24 as Word) == self.read(or_interpret!(self, 797, self.relative(8)))) as Word;
+                    let target = or_interpret!(self, 797, self.writable(63));
+                    self.store(target, value);
+                    // 801: jump_if_true [63], #813
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 801, Address::try_from((813 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 804;
+                }
+                804 => {
+                    // 804: output [797]
+                    let value = self.read(797);
+                    self.instruction_pointer = 806;
+                    return Ok(RunResult::Output(value));
+                }
+                806 => {
+                    // 806: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 806, self.writable(64));
+                    self.store(target, value);
+                    // 810: jump_if_false #0, #813
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 810, Address::try_from((813 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 813;
+                }
+                813 => {
+                    // 813: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 813, self.writable(64));
+                    self.store(target, value);
+                    // 817: add_to_relative_base #5
+                    self.relative_base_offset += (5 as Word);
+                    // 819: equals rb+10, #29, [63]
+                    let value = (self.read(or_interpret!(self, 819, self.relative(10))) == (29 as Word)) as Word;
+                    let target = or_interpret!(self, 819, self.writable(63));
+                    self.store(target, value);
+                    // 823: jump_if_true [63], #829
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 823, Address::try_from((829 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 826;
+                }
+                826 => {
+                    // 826: jump_if_true #1, #835
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 826, Address::try_from((835 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 829;
+                }
+                829 => {
+                    // 829: output [819]
+                    let value = self.read(819);
+                    self.instruction_pointer = 831;
+                    return Ok(RunResult::Output(value));
+                }
+                831 => {
+                    // 831: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 831, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 835;
+                }
+                835 => {
+                    // 835: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 835, self.writable(64));
+                    self.store(target, value);
+                    // 839: add_to_relative_base #7
+                    self.relative_base_offset += (7 as Word);
+                    // 841: less_than #23, rb-4, [63]
+                    let value = ((23 as Word) < self.read(or_interpret!(self, 841, self.relative(-4)))) as Word;
+                    let target = or_interpret!(self, 841, self.writable(63));
+                    self.store(target, value);
+                    // 845: jump_if_true [63], #853
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 845, Address::try_from((853 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 848;
+                }
+                848 => {
+                    // 848: output [841]
+                    let value = self.read(841);
+                    self.instruction_pointer = 850;
+                    return Ok(RunResult::Output(value));
+                }
+                850 => {
+                    // 850: jump_if_true #1, #857
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 850, Address::try_from((857 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 853;
+                }
+                853 => {
+                    // 853: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 853, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 857;
+                }
+                857 => {
+                    // 857: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 857, self.writable(64));
+                    self.store(target, value);
+                    // 861: add_to_relative_base #-2
+                    self.relative_base_offset += (-2 as Word);
+                    // 863: mul rb+0, #1, [63]
+                    let value = self.read(or_interpret!(self, 863, self.relative(0))) * (1 as Word);
+                    let target = or_interpret!(self, 863, self.writable(63));
+                    self.store(target, value);
+                    // 867: equals [63], #21, [63]
+                    let value = (self.read(63) == (21 as Word)) as Word;
+                    let target = or_interpret!(self, 867, self.writable(63));
+                    self.store(target, value);
+                    // 871: jump_if_true [63], #879
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 871, Address::try_from((879 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 874;
+                }
+                874 => {
+                    // 874: output [863]
+                    let value = self.read(863);
+                    self.instruction_pointer = 876;
+                    return Ok(RunResult::Output(value));
+                }
+                876 => {
+                    // 876: jump_if_true #1, #883
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 876, Address::try_from((883 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 879;
+                }
+                879 => {
+                    // 879: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 879, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 883;
+                }
+                883 => {
+                    // 883: mul [64], #2, [64]
+                    let value = self.read(64) * (2 as Word);
+                    let target = or_interpret!(self, 883, self.writable(64));
+                    self.store(target, value);
+                    // 887: add_to_relative_base #15
+                    self.relative_base_offset += (15 as Word);
+                    // 889: jump_if_false #0, rb+8
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 889, Address::try_from(self.read(or_interpret!(self, 889, self.relative(8)))).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 892;
+                }
+                892 => {
+                    // 892: jump_if_false #0, #901
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 892, Address::try_from((901 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 895;
+                }
+                895 => {
+                    // 895: output [889]
+                    let value = self.read(889);
+                    self.instruction_pointer = 897;
+                    return Ok(RunResult::Output(value));
+                }
+                897 => {
+                    // 897: add [64], #1, [64]
+                    let value = self.read(64) + (1 as Word);
+                    let target = or_interpret!(self, 897, self.writable(64));
+                    self.store(target, value);
+                    self.instruction_pointer = 901;
+                }
+                901 => {
+                    // 901: output [64]
+                    let value = self.read(64);
+                    self.instruction_pointer = 903;
+                    return Ok(RunResult::Output(value));
+                }
+                903 => {
+                    // 903: halt
+                    self.instruction_pointer = 903;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                904 => {
+                    // 904: mul #1, #27, rb+1
+                    let value = (1 as Word) * (27 as Word);
+                    let target = or_interpret!(self, 904, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 908: mul #915, #1, rb+0
+                    let value = (915 as Word) * (1 as Word);
+                    let target = or_interpret!(self, 908, self.relative(0).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 912: jump_if_true #1, #922
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 912, Address::try_from((922 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 915;
+                }
+                915 => {
+                    // 915: add rb+1, #51839, rb+1
+                    let value = self.read(or_interpret!(self, 915, self.relative(1))) + (51839 as Word);
+                    let target = or_interpret!(self, 915, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 919: output rb+1
+                    let value = self.read(or_interpret!(self, 919, self.relative(1)));
+                    self.instruction_pointer = 921;
+                    return Ok(RunResult::Output(value));
+                }
+                921 => {
+                    // 921: halt
+                    self.instruction_pointer = 921;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                922 => {
+                    // 922: add_to_relative_base #3
+                    self.relative_base_offset += (3 as Word);
+                    // 924: less_than rb-2, #3, [63]
+                    let value = (self.read(or_interpret!(self, 924, self.relative(-2))) < (3 as Word)) as Word;
+                    let target = or_interpret!(self, 924, self.writable(63));
+                    self.store(target, value);
+                    // 928: jump_if_true [63], #964
+                    if self.read(63) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 928, Address::try_from((964 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 931;
+                }
+                931 => {
+                    // 931: add rb-2, #-1, rb+1
+                    let value = self.read(or_interpret!(self, 931, self.relative(-2))) + (-1 as Word);
+                    let target = or_interpret!(self, 931, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 935: add #942, #0, rb+0
+                    let value = (942 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 935, self.relative(0).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 939: jump_if_false #0, #922
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 939, Address::try_from((922 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 942;
+                }
+                942 => {
+                    // 942: add rb+1, #0, rb-1
+                    let value = self.read(or_interpret!(self, 942, self.relative(1))) + (0 as Word);
+                    let target = or_interpret!(self, 942, self.relative(-1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 946: add rb-2, #-3, rb+1
+                    let value = self.read(or_interpret!(self, 946, self.relative(-2))) + (-3 as Word);
+                    let target = or_interpret!(self, 946, self.relative(1).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 950: add #957, #0, rb+0
+                    let value = (957 as Word) + (0 as Word);
+                    let target = or_interpret!(self, 950, self.relative(0).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 954: jump_if_true #1, #922
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 954, Address::try_from((922 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 957;
+                }
+                957 => {
+                    // 957: add rb+1, rb-1, rb-2
+                    let value = self.read(or_interpret!(self, 957, self.relative(1))) + self.read(or_interpret!(self, 957, self.relative(-1)));
+                    let target = or_interpret!(self, 957, self.relative(-2).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    // 961: jump_if_true #1, #968
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 961, Address::try_from((968 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 964;
+                }
+                964 => {
+                    // 964: add rb-2, #0, rb-2
+                    let value = self.read(or_interpret!(self, 964, self.relative(-2))) + (0 as Word);
+                    let target = or_interpret!(self, 964, self.relative(-2).and_then(|target| self.writable(target)));
+                    self.store(target, value);
+                    self.instruction_pointer = 968;
+                }
+                968 => {
+                    // 968: add_to_relative_base #-3
+                    self.relative_base_offset += (-3 as Word);
+                    // 970: jump_if_false #0, rb+0
+                    if (0 as Word) == 0 {
+                        self.instruction_pointer = or_interpret!(self, 970, Address::try_from(self.read(or_interpret!(self, 970, self.relative(0)))).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 973;
+                }
+                _ => return self.fall_back(),
+            }
+        }
+    }
+}
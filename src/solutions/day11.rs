@@ -1,4 +1,9 @@
-use crate::util::intcode::{parse_intcode_text, Emulator, RunResult};
+use super::INTCODE_TIER;
+use crate::util::intcode::{
+    device::{InputSource, OutputSink},
+    frames::FrameBuffer,
+    parse_intcode_text, Emulator,
+};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use nalgebra::{Point2, Vector2};
@@ -39,73 +44,68 @@ impl Default for Robot {
     }
 }
 
-#[aoc(day11, part1)]
-pub fn part_1(input: &PartInput) -> usize {
-    let mut robot = Robot::default();
-    let mut grid = HashSet::<Point2<i64>>::new();
-    let mut painted_tiles = HashSet::<Point2<i64>>::new();
+/// The hull painting robot, as seen from the intcode brain's side: it reports the color under
+/// the robot, then receives a `[color, turn]` frame.
+#[derive(Default)]
+struct Hull {
+    robot: Robot,
+    grid: HashSet<Point2<i64>>,
+    painted_tiles: HashSet<Point2<i64>>,
+    frame: FrameBuffer<Word, 2>,
+}
 
-    let mut emulator = Emulator::new(input.to_owned());
+impl InputSource<Word> for Hull {
+    fn next_input(&mut self) -> Option<Word> {
+        Some(if self.grid.contains(&self.robot.position) {
+            1
+        } else {
+            0
+        })
+    }
+}
 
-    loop {
-        emulator.push_input(if grid.contains(&robot.position) { 1 } else { 0 });
-        match emulator.run() {
-            RunResult::Output(color) => match color {
-                0 => {
-                    grid.remove(&robot.position);
-                }
-                1 => {
-                    grid.insert(robot.position.clone());
-                    painted_tiles.insert(robot.position.clone());
-                }
-                _ => unreachable!(),
-            },
-            _ => break,
+impl OutputSink<Word> for Hull {
+    fn write_output(&mut self, value: Word) {
+        let [color, turn] = match self.frame.push(value) {
+            Some(frame) => frame,
+            None => return,
+        };
+        match color {
+            0 => {
+                self.grid.remove(&self.robot.position);
+            }
+            1 => {
+                self.grid.insert(self.robot.position);
+                self.painted_tiles.insert(self.robot.position);
+            }
+            _ => unreachable!(),
         }
-        match emulator.run() {
-            RunResult::Output(dir) => match dir {
-                0 => robot.turn_left(),
-                1 => robot.turn_right(),
-                _ => unreachable!(),
-            },
-            _ => break,
+        match turn {
+            0 => self.robot.turn_left(),
+            1 => self.robot.turn_right(),
+            _ => unreachable!(),
         }
     }
+}
 
-    painted_tiles.len()
+fn paint(program: &PartInput, mut hull: Hull) -> Hull {
+    let mut emulator = Emulator::new(program.to_owned());
+    emulator.set_tier(INTCODE_TIER);
+    let stop = emulator.run_device(&mut hull).unwrap();
+    hull.frame.finish(stop).unwrap();
+    hull
+}
+
+#[aoc(day11, part1)]
+pub fn part_1(input: &PartInput) -> usize {
+    paint(input, Hull::default()).painted_tiles.len()
 }
 
 #[aoc(day11, part2)]
 pub fn part_2(input: &PartInput) -> String {
-    let mut robot = Robot::default();
-    let mut grid = HashSet::<Point2<i64>>::new();
-    grid.insert(Point2::new(0, 0));
-
-    let mut emulator = Emulator::new(input.to_owned());
-
-    loop {
-        emulator.push_input(if grid.contains(&robot.position) { 1 } else { 0 });
-        match emulator.run() {
-            RunResult::Output(color) => match color {
-                0 => {
-                    grid.remove(&robot.position);
-                }
-                1 => {
-                    grid.insert(robot.position.clone());
-                }
-                _ => unreachable!(),
-            },
-            _ => break,
-        }
-        match emulator.run() {
-            RunResult::Output(dir) => match dir {
-                0 => robot.turn_left(),
-                1 => robot.turn_right(),
-                _ => unreachable!(),
-            },
-            _ => break,
-        }
-    }
+    let mut hull = Hull::default();
+    hull.grid.insert(Point2::new(0, 0));
+    let grid = paint(input, hull).grid;
 
     let bx = grid.iter().map(|p| p.x).minmax().into_option().unwrap();
     let by = grid.iter().map(|p| p.y).minmax().into_option().unwrap();
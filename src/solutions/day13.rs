@@ -1,4 +1,11 @@
-use crate::util::intcode::{parse_intcode_text, Emulator, RunResult};
+use super::INTCODE_TIER;
+use crate::util::intcode::{
+    device::{InputSource, OutputSink},
+    frames::FrameBuffer,
+    parse_intcode_text,
+    profile::DEFAULT_HOTSPOTS,
+    Emulator, Profiled,
+};
 use aoc_runner_derive::{aoc, aoc_generator};
 use nalgebra::Point2;
 use std::{collections::HashMap, error::Error};
@@ -15,61 +22,76 @@ pub fn generator(input: &[u8]) -> Result<GeneratorOutput, Box<dyn Error>> {
 #[aoc(day13, part1)]
 pub fn part_1(input: &PartInput) -> usize {
     let mut emulator = Emulator::<Word>::new(input.to_owned());
+    emulator.set_checked_arithmetic(true);
+    emulator.set_tier(INTCODE_TIER);
     let mut tile_map = HashMap::<Point2<Word>, Word>::new();
-
-    loop {
-        let x = match emulator.run() {
-            RunResult::Output(val) => val,
-            _ => break,
-        };
-        let y = match emulator.run() {
-            RunResult::Output(val) => val,
-            _ => break,
-        };
-        let tile = match emulator.run() {
-            RunResult::Output(val) => val,
-            _ => break,
-        };
+    for frame in emulator.frames() {
+        let [x, y, tile] = frame.unwrap();
         tile_map.insert(Point2::new(x, y), tile);
     }
 
     tile_map.values().filter(|t| **t == 2).count()
 }
 
-#[aoc(day13, part2)]
-pub fn part_2(input: &PartInput) -> Word {
+/// The arcade cabinet: draws `x, y, tile` triples and moves the joystick towards the ball.
+#[derive(Default)]
+struct Arcade {
+    frame: FrameBuffer<Word, 3>,
+    score: Word,
+    ball_position: Option<Word>,
+    paddle_position: Option<Word>,
+}
+
+impl InputSource<Word> for Arcade {
+    fn next_input(&mut self) -> Option<Word> {
+        match (self.ball_position, self.paddle_position) {
+            (Some(b), Some(p)) => Some((b - p).signum()),
+            _ => Some(0),
+        }
+    }
+}
+
+impl OutputSink<Word> for Arcade {
+    fn write_output(&mut self, value: Word) {
+        match self.frame.push(value) {
+            Some([-1, 0, t]) => self.score = t,
+            Some([x, _, 3]) => self.paddle_position = Some(x),
+            Some([x, _, 4]) => self.ball_position = Some(x),
+            _ => {}
+        }
+    }
+}
+
+/// Plays the game, returning the final score and, if `profile` is set, the profile report.
+fn play(input: &PartInput, profile: bool) -> (Word, Option<String>) {
     let mut memory = input.to_owned();
     memory[0] = 2;
     let mut emulator = Emulator::new(memory);
+    emulator.set_checked_arithmetic(true);
+    emulator.set_tier(INTCODE_TIER);
+    if profile {
+        emulator.start_profile();
+    }
 
-    let mut score = 0;
-    let mut ball_position: Option<Word> = None;
-    let mut paddle_position: Option<Word> = None;
-
-    || -> Option<()> {
-        loop {
-            let mut run_game = || loop {
-                match emulator.run() {
-                    RunResult::Output(val) => break Some(val),
-                    RunResult::InputRequest => match (ball_position, paddle_position) {
-                        (Some(b), Some(p)) => emulator.push_input((b - p).signum()),
-                        _ => emulator.push_input(0),
-                    },
-                    _ => break None,
-                }
-            };
-            let x = run_game()?;
-            let y = run_game()?;
-            let tile = run_game()?;
+    let mut arcade = Arcade::default();
+    let stop = emulator.run_device(&mut arcade).unwrap();
+    arcade.frame.finish(stop).unwrap();
+    let report = emulator
+        .stop_profile()
+        .map(|profile| profile.report(|address| emulator.read_memory(address), DEFAULT_HOTSPOTS));
+    (arcade.score, report)
+}
 
-            match (x, y, tile) {
-                (-1, 0, t) => score = t,
-                (x, _, 3) => paddle_position = Some(x),
-                (x, _, 4) => ball_position = Some(x),
-                _ => {}
-            }
-        }
-    }();
+#[aoc(day13, part2)]
+pub fn part_2(input: &PartInput) -> Word {
+    play(input, false).0
+}
 
-    score
+#[aoc(day13, part2, profiled)]
+pub fn part_2_profiled(input: &PartInput) -> Profiled<Word> {
+    let (score, report) = play(input, true);
+    Profiled {
+        result: score,
+        report: report.unwrap(),
+    }
 }
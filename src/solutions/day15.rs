@@ -1,3 +1,4 @@
+use super::INTCODE_TIER;
 use crate::util::intcode::{parse_intcode_text, Emulator};
 use aoc_runner_derive::{aoc, aoc_generator};
 use arrayvec::ArrayVec;
@@ -48,7 +49,7 @@ fn cardinal_directions() -> impl Iterator<Item = Vector> {
 fn dft(
     position: Point,
     node: NodeIndex,
-    controller: &mut Emulator<Word>,
+    controller: &Emulator<Word>,
     graph: &mut PathGraph,
     map: &mut Map,
     oxygen_pos: &mut Option<Point>,
@@ -63,8 +64,9 @@ fn dft(
             continue;
         }
 
-        controller.push_input(intcode_direction(direction));
-        match controller.run().into_option().unwrap() {
+        let mut probe = controller.fork();
+        probe.push_input(intcode_direction(direction));
+        match probe.run().unwrap().into_option().unwrap() {
             0 => {
                 map.insert(neighbor, None);
             }
@@ -75,9 +77,7 @@ fn dft(
                 let new_node = graph.add_node(neighbor);
                 map.insert(neighbor, Some(new_node));
 
-                dft(neighbor, new_node, controller, graph, map, oxygen_pos);
-                controller.push_input(intcode_direction(-direction));
-                controller.run();
+                dft(neighbor, new_node, &probe, graph, map, oxygen_pos);
             }
         }
     }
@@ -85,6 +85,7 @@ fn dft(
 
 fn build_map(program: &[Word]) -> (PathGraph, Map, Point) {
     let mut controller = Emulator::new(program.to_vec());
+    controller.set_tier(INTCODE_TIER);
     let position = [0, 0].into();
     let mut graph = Graph::default();
     let mut map = HashMap::default();
@@ -95,7 +96,7 @@ fn build_map(program: &[Word]) -> (PathGraph, Map, Point) {
     dft(
         position,
         first_node,
-        &mut controller,
+        &controller,
         &mut graph,
         &mut map,
         &mut oxygen_pos,
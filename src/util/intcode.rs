@@ -1,70 +1,155 @@
 #![allow(unused)]
-use arraydeque::ArrayDeque;
-use num_traits::{
-    AsPrimitive, CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, One, ToPrimitive, Zero,
-};
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Zero};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::error::Error;
-use std::ops::{Add, Div, Index, IndexMut, Mul, Rem};
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::ops::{Add, Index, IndexMut, Mul};
+use std::sync::Arc;
+
+pub mod ascii;
+pub mod assembler;
+pub mod cfg;
+pub mod compiled;
+pub mod device;
+pub mod disassembler;
+pub mod frames;
+pub mod network;
+pub mod profile;
+pub mod snapshot;
+pub mod trace;
+pub mod transpiler;
+pub mod word;
+
+pub use compiled::Tier;
+pub use profile::{Profile, Profiled};
+pub use word::IntcodeWord;
 
 pub type Address = usize;
 
 #[derive(Debug, Default)]
-pub struct Emulator<Word>
-where
-    Word: Copy,
-{
+pub struct Emulator<Word> {
     memory: Memory<Word>,
     instruction_pointer: Address,
     state: State<Word>,
-    relative_base_offset: Address,
-    input_buffer: ArrayDeque<[Word; 8]>,
+    relative_base_offset: Word,
+    input_buffer: VecDeque<Word>,
+    input_capacity: Option<usize>,
+    breakpoints: BTreeSet<Address>,
+    watchpoints: BTreeSet<Address>,
+    paused_at_breakpoint: bool,
+    checked_arithmetic: bool,
+    decode_cache: bool,
+    tier: Tier,
+    blocks: compiled::Blocks<Word>,
+    tracer: Option<trace::Tracer<Word>>,
+    profile: Option<Profile>,
 }
 
-#[derive(Debug, Copy, Clone)]
-enum State<Word>
-where
-    Word: Copy,
-{
+#[derive(Debug, Default, Clone)]
+enum State<Word> {
+    #[default]
     Running,
     Halt,
     RequestingInput(Address),
     HoldingOutput(Word),
 }
 
-impl<Word> Default for State<Word>
-where
-    Word: Copy,
-{
-    fn default() -> Self {
-        Self::Running
-    }
-}
-
-enum OperandMode {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OperandMode {
     Position,
     Immediate,
     Relative,
 }
 
-impl<Word> From<Word> for OperandMode
-where
-    Word: AsPrimitive<usize>,
-{
-    fn from(value: Word) -> Self {
-        match value.as_() {
-            0 => OperandMode::Position,
-            1 => OperandMode::Immediate,
-            2 => OperandMode::Relative,
-            _ => panic!("invalid operand mode encountered"),
-        }
+impl OperandMode {
+    /// The modes by the digit that selects them in an instruction word.
+    const BY_DIGIT: [OperandMode; 3] = [
+        OperandMode::Position,
+        OperandMode::Immediate,
+        OperandMode::Relative,
+    ];
+
+    fn from_digit(digit: usize) -> Option<Self> {
+        Self::BY_DIGIT.get(digit).copied()
+    }
+
+    fn digit(self) -> usize {
+        Self::BY_DIGIT
+            .iter()
+            .position(|&mode| mode == self)
+            .unwrap()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OperandKind {
+    Read,
+    Write,
+}
+
+/// The instructions the emulator understands. Each has an entry in the `instructions!` table,
+/// and the discriminant is its opcode.
+///
+/// Code that handles instructions differently should match on these rather than on mnemonics,
+/// so that missing cases fail to compile.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Opcode {
+    Add = 1,
+    Mul = 2,
+    Input = 3,
+    Output = 4,
+    JumpIfTrue = 5,
+    JumpIfFalse = 6,
+    LessThan = 7,
+    Equals = 8,
+    AddToRelativeBase = 9,
+    Halt = 99,
+}
+
+impl Opcode {
+    pub fn is_jump(self) -> bool {
+        matches!(self, Opcode::JumpIfTrue | Opcode::JumpIfFalse)
+    }
+}
+
+/// Static description of one instruction, generated from the `instructions!` table.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InstructionInfo {
+    pub opcode: Opcode,
+    pub mnemonic: &'static str,
+    pub operands: &'static [OperandKind],
+}
+
+impl InstructionInfo {
+    /// Number of words the instruction occupies, including the opcode.
+    pub fn size(&self) -> usize {
+        1 + self.operands.len()
+    }
+}
+
+pub const MAX_OPERANDS: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub info: &'static InstructionInfo,
+    pub modes: [OperandMode; MAX_OPERANDS],
+}
+
+impl DecodedInstruction {
+    pub fn operand_modes(&self) -> &[OperandMode] {
+        &self.modes[..self.info.operands.len()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RunResult<Word> {
     Halt,
     InputRequest,
     Output(Word),
+    OutOfFuel,
+    Breakpoint(Address),
+    Watchpoint(Address),
 }
 
 impl<Word> RunResult<Word> {
@@ -76,6 +161,135 @@ impl<Word> RunResult<Word> {
     }
 }
 
+/// Everything a program printed before it stopped, and why it stopped.
+///
+/// `stop` is never `RunResult::Output`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outputs<Word> {
+    pub values: Vec<Word>,
+    pub stop: RunResult<Word>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorError<Word> {
+    InvalidOpcode {
+        instruction_pointer: Address,
+        instruction: Word,
+        opcode: Word,
+    },
+    InvalidOperandMode {
+        instruction_pointer: Address,
+        instruction: Word,
+        mode: usize,
+    },
+    ImmediateWrite {
+        instruction_pointer: Address,
+        instruction: Word,
+    },
+    InputQueueFull {
+        capacity: usize,
+        input: Word,
+    },
+    /// Without a memory limit, the address space is `Address::MAX` words. Words too large for
+    /// any address are reported as `Address::MAX`.
+    AddressOutOfRange {
+        instruction_pointer: Address,
+        address: Address,
+        limit: Address,
+    },
+    InputExhausted {
+        instruction_pointer: Address,
+    },
+    NegativeAddress {
+        instruction_pointer: Address,
+        address: Word,
+    },
+    /// Only reported with checked arithmetic, see `Emulator::set_checked_arithmetic`.
+    ArithmeticOverflow {
+        instruction_pointer: Address,
+        operation: &'static str,
+        lhs: Word,
+        rhs: Word,
+    },
+}
+
+impl<Word> fmt::Display for EmulatorError<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmulatorError::InvalidOpcode {
+                instruction_pointer,
+                instruction,
+                opcode,
+            } => write!(
+                f,
+                "invalid opcode {} in instruction {} at address {}",
+                opcode, instruction, instruction_pointer
+            ),
+            EmulatorError::InvalidOperandMode {
+                instruction_pointer,
+                instruction,
+                mode,
+            } => write!(
+                f,
+                "invalid operand mode {} in instruction {} at address {}",
+                mode, instruction, instruction_pointer
+            ),
+            EmulatorError::ImmediateWrite {
+                instruction_pointer,
+                instruction,
+            } => write!(
+                f,
+                "immediate mode write operand in instruction {} at address {}",
+                instruction, instruction_pointer
+            ),
+            EmulatorError::InputQueueFull { capacity, input } => write!(
+                f,
+                "input queue is full ({} values), rejected input {}",
+                capacity, input
+            ),
+            EmulatorError::AddressOutOfRange {
+                instruction_pointer,
+                address,
+                limit,
+            } => write!(
+                f,
+                "address {} is outside the address space of {} words at address {}",
+                address, limit, instruction_pointer
+            ),
+            EmulatorError::InputExhausted {
+                instruction_pointer,
+            } => write!(
+                f,
+                "input requested after all input was consumed at address {}",
+                instruction_pointer
+            ),
+            EmulatorError::NegativeAddress {
+                instruction_pointer,
+                address,
+            } => write!(
+                f,
+                "negative address {} at address {}",
+                address, instruction_pointer
+            ),
+            EmulatorError::ArithmeticOverflow {
+                instruction_pointer,
+                operation,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "overflow in {} of {} and {} at address {}",
+                operation, lhs, rhs, instruction_pointer
+            ),
+        }
+    }
+}
+
+impl<Word> Error for EmulatorError<Word> where Word: fmt::Debug + fmt::Display {}
+
 macro_rules! maybe_pointer_increment {
     ($self:ident, $ip_increment:expr) => {
         $self.instruction_pointer += $ip_increment;
@@ -83,223 +297,871 @@ macro_rules! maybe_pointer_increment {
     ($self:ident) => {};
 }
 
-macro_rules! match_operand {
-    ($self:ident, $name:ident, $instruction:ident, [], $multiplier:expr) => {};
-    ($self:ident, $name:ident, $instruction:ident, [ $par_mode_name:ident, $($rest:ident,)* ], $multiplier:expr) => {
-        let $par_mode_name = $crate::util::intcode::OperandMode::from(($instruction.as_() / $multiplier) % 10);
-        match_operand!($self, $name, $instruction, [ $($rest,)* ], $multiplier * 10);
+macro_rules! instruction_info {
+    ($opcode:expr, $name:ident, [ $($operand_name:ident)* ], [ $($write_operand_name:ident)* ]) => {
+        InstructionInfo {
+            opcode: $opcode,
+            mnemonic: stringify!($name),
+            operands: &[
+                $(instruction_info!(@kind Read $operand_name),)*
+                $(instruction_info!(@kind Write $write_operand_name),)*
+            ],
+        }
+    };
+    (@kind $kind:ident $operand_name:ident) => {
+        OperandKind::$kind
     };
 }
 
 macro_rules! instructions {
     (
         $(
-            $opcode:expr => $name:ident
+            $opcode:path => $name:ident
                 ([ $($operand_name:ident + $operand_offset:expr),* ],
                     [ $($write_operand_name:ident + $write_operand_offset:expr),* ]
                     $($ip_increment:tt)*
                 )
                 $code:block
         )*
-        => $run_instruction:ident ();
+        => $self:ident . $run_instruction:ident ();
     ) => {
-        paste::item!{
+        /// Every instruction the emulator understands.
+        pub const INSTRUCTIONS: &'static [InstructionInfo] = &[
+            $(instruction_info!($opcode, $name, [$($operand_name)*], [$($write_operand_name)*]),)*
+        ];
+
+        pub fn instruction_info(opcode: usize) -> Option<&'static InstructionInfo> {
             $(
-                fn $name( &mut self, $([<__ $operand_name _mode>]: $crate::util::intcode::OperandMode,)* $([<__ $write_operand_name _mode>]: $crate::util::intcode::OperandMode,)* ) {
-                    $(let [<$operand_name>] = self.get_operand(self.memory[self.instruction_pointer + $operand_offset], [<__ $operand_name _mode>]);)*
-                    $(let [<$write_operand_name>] = self.get_operand_address(self.memory[self.instruction_pointer + $write_operand_offset], [<__ $write_operand_name _mode>]);)*
-                    $code;
-                    maybe_pointer_increment!(self $($ip_increment)*);
+                if opcode == $opcode as usize {
+                    return Some(&instruction_info!($opcode, $name, [$($operand_name)*], [$($write_operand_name)*]));
                 }
             )*
-            fn $run_instruction(&mut self, instruction: Word) {
-                let opcode = instruction.as_() % 100;
-                match opcode {
-                    $(
-                        $opcode => {
-                            match_operand!(self, $name, instruction, [$([<__ $operand_name _mode>],)* $([<__ $write_operand_name _mode>],)*], 100);
-                            self.$name($([<__ $operand_name _mode>],)* $([<__ $write_operand_name _mode>],)*);
-                        },
-                    )*
-                    _ => panic!("invalid opcode encountered"),
+            None
+        }
+
+        $(
+            fn $name(&mut $self, modes: [OperandMode; MAX_OPERANDS]) -> Result<(), EmulatorError<Word>> {
+                let mut modes = modes.iter().cloned();
+                $(let $operand_name = $self.get_operand(&$self.memory[$self.instruction_pointer + $operand_offset], modes.next().unwrap())?;)*
+                $(let $write_operand_name = $self.get_operand_address(&$self.memory[$self.instruction_pointer + $write_operand_offset], modes.next().unwrap())?;)*
+                if let Some(tracer) = $self.tracer.as_mut() {
+                    tracer.operands.clear();
+                    $(tracer.operands.push($operand_name.clone());)*
                 }
+                $code;
+                maybe_pointer_increment!($self $($ip_increment)*);
+                Ok(())
+            }
+        )*
+
+        fn execute(&mut $self, decoded: DecodedInstruction) -> Result<(), EmulatorError<Word>> {
+            match decoded.info.opcode {
+                $(
+                    $opcode => $self.$name(decoded.modes),
+                )*
             }
         }
+
+        fn $run_instruction(&mut $self, instruction: &Word) -> Result<(), EmulatorError<Word>> {
+            let decoded = Self::decode_instruction(instruction, $self.instruction_pointer)?;
+            $self.execute(decoded)
+        }
     };
 }
 
 impl<Word> Emulator<Word>
 where
-    Word: Copy
-        + Clone
-        + AsPrimitive<Address>
-        + ToPrimitive
-        + FromPrimitive
-        + Add<Output = Word>
-        + Mul<Output = Word>
-        + Div<Output = Word>
-        + Rem<Output = Word>
-        + Zero
-        + One
-        + Eq
-        + Ord,
+    Word: IntcodeWord,
 {
     instructions! {
-        1 => add ([a + 1, b + 2], [write + 3], 4) {
-            self.memory[write] = a + b;
+        Opcode::Add => add ([a + 1, b + 2], [write + 3], 4) {
+            self.memory[write] = self.arithmetic("add", a, b, Word::checked_add, Add::add)?;
         }
-        2 => mul ([a + 1, b + 2], [write + 3], 4) {
-            self.memory[write] = a * b;
+        Opcode::Mul => mul ([a + 1, b + 2], [write + 3], 4) {
+            self.memory[write] = self.arithmetic("mul", a, b, Word::checked_mul, Mul::mul)?;
         }
-        3 => input ([], [write + 1], 2) {
-            self.state = State::RequestingInput(write.as_());
+        Opcode::Input => input ([], [write + 1], 2) {
+            self.state = State::RequestingInput(write);
         }
-        4 => output ([read + 1], [], 2) {
+        Opcode::Output => output ([read + 1], [], 2) {
             self.state = State::HoldingOutput(read);
         }
-        5 => jump_if_true ([test + 1, jump + 2], []) {
-            match test.as_() {
-                0 => self.instruction_pointer += 3,
-                _ => self.instruction_pointer = jump.as_(),
+        Opcode::JumpIfTrue => jump_if_true ([test + 1, jump + 2], []) {
+            if test.is_zero() {
+                self.instruction_pointer += 3;
+            } else {
+                self.instruction_pointer = self.word_to_address(&jump)?;
             }
         }
-        6 => jump_if_false ([test + 1, jump + 2], []) {
-            match test.as_() {
-                0 => self.instruction_pointer = jump.as_(),
-                _ => self.instruction_pointer += 3,
+        Opcode::JumpIfFalse => jump_if_false ([test + 1, jump + 2], []) {
+            if test.is_zero() {
+                self.instruction_pointer = self.word_to_address(&jump)?;
+            } else {
+                self.instruction_pointer += 3;
             }
         }
-        7 => less_than ([a + 1, b + 2], [write + 3], 4) {
+        Opcode::LessThan => less_than ([a + 1, b + 2], [write + 3], 4) {
             self.memory[write] = if a < b {Word::one()} else {Word::zero()}
         }
-        8 => equals ([a + 1, b + 2], [write + 3], 4) {
+        Opcode::Equals => equals ([a + 1, b + 2], [write + 3], 4) {
             self.memory[write] = if a == b {Word::one()} else {Word::zero()}
         }
-        9 => add_to_relative_base ([rbo + 1], [], 2) {
-            self.relative_base_offset = (Word::from_usize(self.relative_base_offset).unwrap() + rbo).as_()
+        Opcode::AddToRelativeBase => add_to_relative_base ([rbo + 1], [], 2) {
+            let base = self.relative_base_offset.clone();
+            self.relative_base_offset = self.arithmetic("add_to_relative_base", base, rbo, Word::checked_add, Add::add)?;
         }
-        99 => halt ([], [], 1) {
+        Opcode::Halt => halt ([], [], 1) {
             self.state = State::Halt;
         }
-        => run_instruction();
+        => self.run_instruction();
     }
 
-    fn get_operand(&mut self, value: Word, mode: OperandMode) -> Word {
-        match mode {
-            OperandMode::Position => self.memory[value.as_()].clone(),
-            OperandMode::Immediate => value,
-            OperandMode::Relative => self.memory
-                [(Word::from_usize(self.relative_base_offset).unwrap() + value).as_()]
-            .clone(),
+    /// Splits an instruction word into its opcode and operand modes.
+    ///
+    /// `instruction_pointer` is only used to fill in the error.
+    pub fn decode_instruction(
+        instruction: &Word,
+        instruction_pointer: Address,
+    ) -> Result<DecodedInstruction, EmulatorError<Word>> {
+        let (ten, hundred) = (Word::from_u8(10).unwrap(), Word::from_u8(100).unwrap());
+        let opcode = instruction.clone() % hundred.clone();
+        let info = match opcode.to_address().and_then(Self::instruction_info) {
+            Some(info) => info,
+            None => {
+                return Err(EmulatorError::InvalidOpcode {
+                    instruction_pointer,
+                    instruction: instruction.clone(),
+                    opcode,
+                })
+            }
+        };
+        let mut modes = [OperandMode::Position; MAX_OPERANDS];
+        let mut digits = instruction.clone() / hundred;
+        for mode in modes.iter_mut().take(info.operands.len()) {
+            // negative instructions have no valid opcode, so the digit is never negative
+            let digit = (digits.clone() % ten.clone()).to_address().unwrap();
+            *mode = OperandMode::from_digit(digit).ok_or(EmulatorError::InvalidOperandMode {
+                instruction_pointer,
+                instruction: instruction.clone(),
+                mode: digit,
+            })?;
+            digits = digits / ten.clone();
+        }
+        Ok(DecodedInstruction { info, modes })
+    }
+
+    /// Applies `unchecked`, or `checked` if checked arithmetic is on, turning overflow into
+    /// an error.
+    fn arithmetic(
+        &self,
+        operation: &'static str,
+        lhs: Word,
+        rhs: Word,
+        checked: fn(&Word, &Word) -> Option<Word>,
+        unchecked: fn(Word, Word) -> Word,
+    ) -> Result<Word, EmulatorError<Word>> {
+        if !self.checked_arithmetic {
+            return Ok(unchecked(lhs, rhs));
+        }
+        checked(&lhs, &rhs).ok_or(EmulatorError::ArithmeticOverflow {
+            instruction_pointer: self.instruction_pointer,
+            operation,
+            lhs,
+            rhs,
+        })
+    }
+
+    /// Turns a word into an address, refusing negative ones and ones too large for an address
+    /// instead of letting them wrap.
+    fn word_to_address(&self, word: &Word) -> Result<Address, EmulatorError<Word>> {
+        if *word < Word::zero() {
+            return Err(EmulatorError::NegativeAddress {
+                instruction_pointer: self.instruction_pointer,
+                address: word.clone(),
+            });
+        }
+        word.to_address()
+            .ok_or_else(|| self.address_out_of_range(Address::MAX))
+    }
+
+    fn check_address(&self, address: Address) -> Result<Address, EmulatorError<Word>> {
+        if address >= self.memory.limit.unwrap_or(Address::MAX) {
+            return Err(self.address_out_of_range(address));
         }
+        Ok(address)
     }
-    fn get_operand_address(&mut self, value: Word, mode: OperandMode) -> Address {
+
+    fn address_out_of_range(&self, address: Address) -> EmulatorError<Word> {
+        EmulatorError::AddressOutOfRange {
+            instruction_pointer: self.instruction_pointer,
+            address,
+            limit: self.memory.limit.unwrap_or(Address::MAX),
+        }
+    }
+
+    fn get_operand(&self, value: &Word, mode: OperandMode) -> Result<Word, EmulatorError<Word>> {
         match mode {
-            OperandMode::Position => value.as_(),
-            OperandMode::Immediate => panic!(),
-            OperandMode::Relative => {
-                (Word::from_usize(self.relative_base_offset).unwrap() + value).as_()
+            OperandMode::Immediate => Ok(value.clone()),
+            _ => {
+                let address = self.get_operand_address(value, mode)?;
+                Ok(self.memory[address].clone())
             }
         }
     }
+    fn get_operand_address(
+        &self,
+        value: &Word,
+        mode: OperandMode,
+    ) -> Result<Address, EmulatorError<Word>> {
+        match mode {
+            OperandMode::Position => self.check_address(self.word_to_address(value)?),
+            OperandMode::Immediate => Err(EmulatorError::ImmediateWrite {
+                instruction_pointer: self.instruction_pointer,
+                instruction: self.memory[self.instruction_pointer].clone(),
+            }),
+            OperandMode::Relative => self.relative_address(value),
+        }
+    }
+
+    fn relative_address(&self, offset: &Word) -> Result<Address, EmulatorError<Word>> {
+        let address = self.arithmetic(
+            "relative address",
+            self.relative_base_offset.clone(),
+            offset.clone(),
+            Word::checked_add,
+            Add::add,
+        )?;
+        self.check_address(self.word_to_address(&address)?)
+    }
 
     pub fn new(memory: Vec<Word>) -> Self {
-        Self {
+        let mut emulator = Self {
             memory: Memory::new(memory),
             instruction_pointer: 0,
             state: State::Running,
-            relative_base_offset: 0,
-            input_buffer: Default::default(),
+            relative_base_offset: Word::zero(),
+            input_buffer: VecDeque::new(),
+            input_capacity: None,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            paused_at_breakpoint: false,
+            checked_arithmetic: false,
+            decode_cache: true,
+            tier: Tier::Interpreter,
+            blocks: compiled::Blocks::default(),
+            tracer: None,
+            profile: None,
+        };
+        emulator.predecode();
+        emulator
+    }
+
+    /// Creates an emulator that refuses to touch any address at or above `limit`.
+    pub fn with_memory_limit(memory: Vec<Word>, limit: Address) -> Self {
+        let mut emulator = Self::new(memory);
+        emulator.set_memory_limit(Some(limit));
+        emulator
+    }
+
+    pub fn set_memory_limit(&mut self, limit: Option<Address>) {
+        self.memory.limit = limit;
+        // blocks were only compiled as far as the old limit allowed
+        self.drop_compiled_code();
+    }
+
+    /// Makes `add`, `mul` and relative addressing fail with `EmulatorError::ArithmeticOverflow`
+    /// instead of overflowing the word type.
+    ///
+    /// Off by default, where overflow does whatever `+` and `*` of the word type do: primitive
+    /// integers panic in debug builds and wrap in release builds.
+    pub fn set_checked_arithmetic(&mut self, checked: bool) {
+        self.checked_arithmetic = checked;
+    }
+
+    /// Turns the cache of decoded instructions on or off.
+    ///
+    /// With the cache, every instruction word of the program is split into opcode and operand
+    /// modes once, up front, instead of each time it is executed. Writing to an address drops
+    /// its entry until the new instruction there runs. On by default.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.decode_cache = enabled;
+        if enabled {
+            self.predecode();
+        } else {
+            self.memory.decoded = Arc::default();
+        }
+    }
+
+    /// Fills the decode cache for the program image, leaving out words written far past it.
+    /// Forks share the cache until one of them changes its code.
+    fn predecode(&mut self) {
+        let memory = &self.memory;
+        let decoded = (0..memory.image_end())
+            .map(|address| Self::decode_instruction(&memory[address], address).ok())
+            .collect();
+        self.memory.decoded = Arc::new(decoded);
+    }
+
+    /// Creates an emulator whose input queue holds at most `capacity` values.
+    pub fn with_input_capacity(memory: Vec<Word>, capacity: usize) -> Self {
+        let mut emulator = Self::new(memory);
+        emulator.input_buffer.reserve(capacity);
+        emulator.input_capacity = Some(capacity);
+        emulator
+    }
+
+    /// Continues a program that was running somewhere else, from its memory and registers.
+    pub fn resume(
+        memory: Vec<Word>,
+        instruction_pointer: Address,
+        relative_base_offset: Word,
+    ) -> Self {
+        let mut emulator = Self::new(memory);
+        emulator.instruction_pointer = instruction_pointer;
+        emulator.relative_base_offset = relative_base_offset;
+        emulator
+    }
+
+    /// Creates an independent copy of this machine, including pending input and breakpoints.
+    ///
+    /// Memory pages are shared until either side writes to them, so forking costs about as
+    /// much as cloning the page table. The fork does not inherit an active trace or profile.
+    pub fn fork(&self) -> Self {
+        Self {
+            memory: self.memory.clone(),
+            instruction_pointer: self.instruction_pointer,
+            state: self.state.clone(),
+            relative_base_offset: self.relative_base_offset.clone(),
+            input_buffer: self.input_buffer.clone(),
+            input_capacity: self.input_capacity,
+            breakpoints: self.breakpoints.clone(),
+            watchpoints: self.watchpoints.clone(),
+            paused_at_breakpoint: self.paused_at_breakpoint,
+            checked_arithmetic: self.checked_arithmetic,
+            decode_cache: self.decode_cache,
+            tier: self.tier,
+            blocks: self.blocks.clone(),
+            tracer: None,
+            profile: None,
         }
     }
 
-    pub fn run(&mut self) -> RunResult<Word> {
+    pub fn run(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        self.run_with_fuel(None)
+    }
+
+    /// Like `run`, but returns `RunResult::OutOfFuel` after executing `budget` instructions.
+    ///
+    /// The emulator is left in a consistent state, so calling `run` or `run_with_budget` again
+    /// resumes right where it stopped.
+    pub fn run_with_budget(
+        &mut self,
+        budget: usize,
+    ) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        self.run_with_fuel(Some(budget))
+    }
+
+    /// Executes a single instruction, ignoring breakpoints.
+    ///
+    /// Returns `RunResult::OutOfFuel` if the instruction ran without causing any other event.
+    pub fn step(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        let paused = self.paused_at_breakpoint;
+        self.paused_at_breakpoint = true;
+        let result = self.run_with_fuel(Some(1));
+        // still set if no instruction ran, say while waiting for input: the breakpoint at the
+        // instruction pointer hasn't been passed yet
+        if self.paused_at_breakpoint {
+            self.paused_at_breakpoint = paused;
+        }
+        result
+    }
+
+    fn run_with_fuel(
+        &mut self,
+        mut fuel: Option<usize>,
+    ) -> Result<RunResult<Word>, EmulatorError<Word>> {
         loop {
             match self.state.clone() {
                 State::HoldingOutput(output) => {
                     self.state = State::Running;
-                    return RunResult::Output(output);
+                    return Ok(RunResult::Output(output));
                 }
                 State::RequestingInput(address) => {
                     if let Some(input) = self.input_buffer.pop_front() {
+                        if let Some(tracer) = self.tracer.as_mut() {
+                            if let Some(mut entry) = tracer.pending.take() {
+                                entry.write = Some((address, input.clone()));
+                                tracer.emit(entry);
+                            }
+                        }
                         self.memory[address] = input;
+                        if let Some(profile) = self.profile.as_mut() {
+                            profile.count_write(address);
+                        }
                         self.state = State::Running;
+                        if self.watchpoints.contains(&address) {
+                            return Ok(RunResult::Watchpoint(address));
+                        }
                     } else {
-                        return RunResult::InputRequest;
+                        return Ok(RunResult::InputRequest);
                     }
                 }
                 State::Halt => {
-                    return RunResult::Halt;
+                    return Ok(RunResult::Halt);
                 }
                 _ => {}
             }
-            self.run_instruction(self.memory[self.instruction_pointer].clone());
+            if fuel.is_none() && self.can_run_compiled() {
+                self.run_compiled()?;
+                if !matches!(self.state, State::Running) {
+                    continue;
+                }
+            }
+            if let Some(fuel) = fuel.as_mut() {
+                if *fuel == 0 {
+                    return Ok(RunResult::OutOfFuel);
+                }
+                *fuel -= 1;
+            }
+            if !self.breakpoints.is_empty()
+                && !self.paused_at_breakpoint
+                && self.breakpoints.contains(&self.instruction_pointer)
+            {
+                self.paused_at_breakpoint = true;
+                return Ok(RunResult::Breakpoint(self.instruction_pointer));
+            }
+            self.check_address(self.instruction_pointer)?;
+            self.memory.last_write = None;
+            let instruction_pointer = self.instruction_pointer;
+            let decoded = self.decode_at(instruction_pointer)?;
+            if self.profile.is_some() {
+                self.record_profile(instruction_pointer, decoded);
+            }
+            self.execute(decoded)?;
+            self.paused_at_breakpoint = false;
+            if self.tracer.is_some() {
+                self.record_trace(instruction_pointer, decoded.info);
+            }
+            if let (Some(profile), Some(address)) = (self.profile.as_mut(), self.memory.last_write)
+            {
+                profile.count_write(address);
+            }
+            if let Some(address) = self.memory.last_write {
+                if self.watchpoints.contains(&address) {
+                    return Ok(RunResult::Watchpoint(address));
+                }
+            }
+        }
+    }
+
+    /// Decodes the instruction at `instruction_pointer`, going through the decode cache.
+    fn decode_at(
+        &mut self,
+        instruction_pointer: Address,
+    ) -> Result<DecodedInstruction, EmulatorError<Word>> {
+        if let Some(&Some(decoded)) = self.memory.decoded.get(instruction_pointer) {
+            return Ok(decoded);
         }
+        let decoded =
+            Self::decode_instruction(&self.memory[instruction_pointer], instruction_pointer)?;
+        // code outside the program image is rare, and caching it could take a lot of space
+        if self.decode_cache && instruction_pointer < self.memory.decoded.len() {
+            Arc::make_mut(&mut self.memory.decoded)[instruction_pointer] = Some(decoded);
+        }
+        Ok(decoded)
+    }
+
+    fn record_trace(&mut self, instruction_pointer: Address, info: &'static InstructionInfo) {
+        let write = self
+            .memory
+            .last_write
+            .map(|address| (address, self.memory[address].clone()));
+        let relative_base_offset = self.relative_base_offset.clone();
+        let waiting_for_input = matches!(self.state, State::RequestingInput(_));
+        let tracer = self.tracer.as_mut().unwrap();
+        let entry = trace::TraceEntry {
+            instruction_pointer,
+            instruction: info,
+            operands: tracer.operands.clone(),
+            write,
+            relative_base_offset,
+        };
+        if waiting_for_input {
+            tracer.pending = Some(entry);
+        } else {
+            tracer.emit(entry);
+        }
+    }
+
+    /// Counts the execution of `decoded` and the memory it is about to read.
+    fn record_profile(&mut self, instruction_pointer: Address, decoded: DecodedInstruction) {
+        let mut reads = [None; MAX_OPERANDS];
+        let operands = decoded.info.operands.iter().zip(decoded.operand_modes());
+        for (i, (&kind, &mode)) in operands.enumerate() {
+            if kind == OperandKind::Read && mode != OperandMode::Immediate {
+                let value = &self.memory[instruction_pointer + 1 + i];
+                reads[i] = self.get_operand_address(value, mode).ok();
+            }
+        }
+        let profile = self.profile.as_mut().unwrap();
+        profile.count_execution(instruction_pointer, decoded.info.opcode);
+        for &address in reads.iter().flatten() {
+            profile.count_read(address);
+        }
+    }
+
+    /// Starts counting executed instructions and memory accesses, discarding any earlier
+    /// profile.
+    ///
+    /// Compiled code doesn't run while profiling; see `Profile` for what is counted.
+    pub fn start_profile(&mut self) {
+        self.profile = Some(Profile::default());
+    }
+
+    /// The counts collected since `start_profile`.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Stops profiling and returns the counts.
+    pub fn stop_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    /// Starts writing a line to `writer` for every executed instruction.
+    ///
+    /// See `trace` for the format. Write errors don't interrupt the program; the first one is
+    /// returned by `stop_trace`.
+    pub fn trace_to(&mut self, writer: impl Write + Send + 'static)
+    where
+        Word: fmt::Display + 'static,
+    {
+        self.tracer = Some(trace::Tracer::new(Box::new(BufWriter::new(writer))));
+    }
+
+    /// Stops tracing and flushes the trace.
+    pub fn stop_trace(&mut self) -> io::Result<()> {
+        match self.tracer.take() {
+            Some(tracer) => tracer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Makes `run` stop with `RunResult::Breakpoint` before executing the instruction at `address`.
+    pub fn add_breakpoint(&mut self, address: Address) -> bool {
+        self.breakpoints.insert(address)
+    }
+
+    pub fn remove_breakpoint(&mut self, address: Address) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = Address> + '_ {
+        self.breakpoints.iter().cloned()
+    }
+
+    /// Makes `run` stop with `RunResult::Watchpoint` right after an instruction writes to `address`.
+    pub fn add_watchpoint(&mut self, address: Address) -> bool {
+        self.watchpoints.insert(address)
+    }
+
+    pub fn remove_watchpoint(&mut self, address: Address) -> bool {
+        self.watchpoints.remove(&address)
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = Address> + '_ {
+        self.watchpoints.iter().cloned()
+    }
+
+    pub fn instruction_pointer(&self) -> Address {
+        self.instruction_pointer
+    }
+
+    pub fn relative_base_offset(&self) -> Word {
+        self.relative_base_offset.clone()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        matches!(self.state, State::Halt)
     }
 
+    pub fn read_memory(&self, address: Address) -> Word {
+        self.memory[address].clone()
+    }
+
+    pub fn write_memory(&mut self, address: Address, value: Word) {
+        self.memory[address] = value;
+    }
+
+    /// One past the highest address holding a word of the program or a written value.
+    ///
+    /// Memory below this isn't necessarily allocated; see `into_memory`.
+    pub fn memory_len(&self) -> Address {
+        self.memory.end
+    }
+
+    /// Queues a value for the next input instruction.
+    ///
+    /// Panics if the emulator was created with a bounded input queue that is already full.
     pub fn push_input(&mut self, input: Word) {
-        self.input_buffer.push_back(input).unwrap()
+        if let Err(EmulatorError::InputQueueFull { capacity, .. }) = self.try_push_input(input) {
+            panic!("input queue is full ({} values)", capacity);
+        }
+    }
+
+    pub fn try_push_input(&mut self, input: Word) -> Result<(), EmulatorError<Word>> {
+        match self.input_capacity {
+            Some(capacity) if self.input_buffer.len() >= capacity => {
+                Err(EmulatorError::InputQueueFull { capacity, input })
+            }
+            _ => {
+                self.input_buffer.push_back(input);
+                Ok(())
+            }
+        }
     }
 
+    /// Queues all values from `input`, panicking like `push_input` if a bounded queue overflows.
     pub fn extend_input(&mut self, input: impl IntoIterator<Item = Word>) {
-        self.input_buffer.extend(input);
+        for value in input {
+            self.push_input(value);
+        }
+    }
+
+    pub fn pending_input(&self) -> usize {
+        self.input_buffer.len()
     }
 
-    pub fn into_memory(self) -> Vec<Word> {
+    /// The allocated memory as runs of consecutive words, each with its start address, in
+    /// address order.
+    ///
+    /// The first run starts at 0 and holds the program image. Words written far past it come
+    /// in runs of their own, and the unallocated gaps between runs read as zero, so a single
+    /// write to a huge address costs one page here too.
+    pub fn into_memory(self) -> Vec<(Address, Vec<Word>)> {
         self.memory.into_inner()
     }
+
+    /// Iterates over the program's outputs until it halts or needs more input.
+    ///
+    /// After the iterator ends, `OutputIter::stop` tells which of the two happened.
+    pub fn outputs(&mut self) -> OutputIter<'_, Word> {
+        OutputIter {
+            emulator: self,
+            stop: None,
+            failed: false,
+        }
+    }
+
+    /// Runs until the program halts or asks for input that isn't queued, collecting outputs.
+    pub fn run_until_input(&mut self) -> Result<Outputs<Word>, EmulatorError<Word>> {
+        let mut values = Vec::new();
+        let stop = self.run_with(device::from_iter(None), &mut values)?;
+        Ok(Outputs { values, stop })
+    }
+
+    /// Feeds `inputs` to the program and runs it until it halts, returning all of its outputs.
+    ///
+    /// Breakpoints and watchpoints are ignored. Fails with `EmulatorError::InputExhausted` if
+    /// the program asks for more input than it was given, and with
+    /// `EmulatorError::InputQueueFull` if the inputs don't fit in a bounded input queue.
+    pub fn run_to_completion(
+        &mut self,
+        inputs: impl IntoIterator<Item = Word>,
+    ) -> Result<Vec<Word>, EmulatorError<Word>> {
+        for input in inputs {
+            self.try_push_input(input)?;
+        }
+        let mut values = Vec::new();
+        loop {
+            let mut outputs = self.run_until_input()?;
+            values.append(&mut outputs.values);
+            match outputs.stop {
+                RunResult::Halt => return Ok(values),
+                RunResult::InputRequest => {
+                    // the input instruction has already moved the pointer past itself
+                    let input_size = Self::instruction_info(Opcode::Input as usize)
+                        .unwrap()
+                        .size();
+                    return Err(EmulatorError::InputExhausted {
+                        instruction_pointer: self.instruction_pointer - input_size,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
-#[derive(Debug, Default)]
-struct Memory<Word>
+/// Iterator returned by `Emulator::outputs`.
+pub struct OutputIter<'a, Word> {
+    emulator: &'a mut Emulator<Word>,
+    stop: Option<RunResult<Word>>,
+    failed: bool,
+}
+
+impl<Word> OutputIter<'_, Word> {
+    /// Why the iterator ended: `RunResult::Halt`, `RunResult::InputRequest`, or a breakpoint or
+    /// watchpoint. `None` while it is still running or if it ended with an error.
+    pub fn stop(&self) -> Option<&RunResult<Word>> {
+        self.stop.as_ref()
+    }
+}
+
+impl<Word> Iterator for OutputIter<'_, Word>
 where
-    Word: Copy,
+    Word: IntcodeWord,
 {
-    inner: Vec<Word>,
+    type Item = Result<Word, EmulatorError<Word>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stop.is_some() || self.failed {
+            return None;
+        }
+        match self.emulator.run() {
+            Ok(RunResult::Output(value)) => Some(Ok(value)),
+            Ok(stop) => {
+                self.stop = Some(stop);
+                None
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+const PAGE_SIZE: usize = 1024;
+
+type Page<Word> = Arc<Vec<Word>>;
+
+/// Intcode memory, split into pages that are shared between forks and copied on first write.
+///
+/// The program image is kept as a dense run of pages indexed directly; everything past its
+/// end is allocated lazily, so a single far-off write costs one page instead of gigabytes.
+#[derive(Debug, Default, Clone)]
+struct Memory<Word> {
+    image: Vec<Page<Word>>,
+    pages: HashMap<usize, Page<Word>>,
+    end: Address,
+    limit: Option<Address>,
+    last_write: Option<Address>,
+    /// Decoded instructions by address, see `Emulator::set_decode_cache`. A write to an address
+    /// drops its entry.
+    decoded: Arc<Vec<Option<DecodedInstruction>>>,
+    /// Marks every word that is part of a compiled block.
+    code: Arc<Vec<bool>>,
+    /// Marked words written to since the compiled blocks were last checked.
+    modified_code: Vec<Address>,
     zero: Word,
 }
 
-impl<Word> Index<Address> for Memory<Word>
-where
-    Word: Copy,
-{
+impl<Word> Index<Address> for Memory<Word> {
     type Output = Word;
 
     fn index(&self, index: Address) -> &Self::Output {
-        self.inner.get(index).unwrap_or(&self.zero)
+        let (page, offset) = (index / PAGE_SIZE, index % PAGE_SIZE);
+        if let Some(words) = self.image.get(page) {
+            return &words[offset];
+        }
+        self.pages
+            .get(&page)
+            .map_or(&self.zero, |words| &words[offset])
     }
 }
 
 impl<Word> IndexMut<Address> for Memory<Word>
 where
-    Word: Copy,
+    Word: Clone,
 {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        if self.inner.len() <= index {
-            self.inner.resize(index + 1, self.zero.clone());
+        self.last_write = Some(index);
+        if let Some(Some(_)) = self.decoded.get(index) {
+            Arc::make_mut(&mut self.decoded)[index] = None;
         }
-        self.inner.get_mut(index).unwrap()
+        if let Some(true) = self.code.get(index) {
+            self.modified_code.push(index);
+        }
+        self.end = self.end.max(index.saturating_add(1));
+        let (page, offset) = (index / PAGE_SIZE, index % PAGE_SIZE);
+        let zero = &self.zero;
+        let words = match self.image.get_mut(page) {
+            Some(words) => words,
+            None => self
+                .pages
+                .entry(page)
+                .or_insert_with(|| Arc::new(vec![zero.clone(); PAGE_SIZE])),
+        };
+        &mut Arc::make_mut(words)[offset]
     }
 }
 
 impl<Word> Memory<Word>
 where
-    Word: Copy + Zero,
+    Word: Clone + Zero,
 {
     fn new(memory: Vec<Word>) -> Self {
+        let zero = Word::zero();
+        let end = memory.len();
+        let image = memory
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut words = chunk.to_vec();
+                words.resize(PAGE_SIZE, zero.clone());
+                Arc::new(words)
+            })
+            .collect();
         Self {
-            inner: memory,
-            zero: Word::zero(),
+            image,
+            pages: HashMap::new(),
+            end,
+            limit: None,
+            last_write: None,
+            decoded: Arc::default(),
+            code: Arc::default(),
+            modified_code: Vec::new(),
+            zero,
         }
     }
 }
 
 impl<Word> Memory<Word>
 where
-    Word: Copy,
+    Word: Clone,
 {
-    fn into_inner(self) -> Vec<Word> {
-        self.inner
+    /// One past the last word of the program image that is in use. The decode cache and
+    /// compiled code stop here, so a far write doesn't make them cover the gap.
+    fn image_end(&self) -> Address {
+        self.end.min(self.image.len() * PAGE_SIZE)
+    }
+
+    /// Iterates over every allocated page with its page number, image pages first.
+    fn allocated_pages(&self) -> impl Iterator<Item = (usize, &[Word])> {
+        self.image
+            .iter()
+            .enumerate()
+            .chain(self.pages.iter().map(|(&page, words)| (page, words)))
+            .map(|(page, words)| (page, &words[..]))
+    }
+
+    /// Joins the allocated pages into runs of consecutive words, cut off at `end`.
+    fn into_inner(self) -> Vec<(Address, Vec<Word>)> {
+        let mut pages: Vec<_> = self.allocated_pages().collect();
+        pages.sort_by_key(|&(page, _)| page);
+        let mut runs: Vec<(Address, Vec<Word>)> = Vec::new();
+        for (page, words) in pages {
+            let base = page * PAGE_SIZE;
+            if base >= self.end {
+                continue;
+            }
+            let words = &words[..PAGE_SIZE.min(self.end - base)];
+            match runs.last_mut() {
+                Some((start, run)) if *start + run.len() == base => run.extend_from_slice(words),
+                _ => runs.push((base, words.to_vec())),
+            }
+        }
+        runs
     }
 }
 
@@ -315,3 +1177,637 @@ where
             .1,
     )
 }
+
+/// Loads a program from a path, or from `input/2019/day<N>.txt` if given a day number.
+pub fn load_intcode_program<Word>(arg: &str) -> Result<Vec<Word>, Box<dyn Error>>
+where
+    Word: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+{
+    let path = match arg.parse::<u32>() {
+        Ok(day) => format!("input/2019/day{}.txt", day),
+        Err(_) => arg.to_owned(),
+    };
+    let text = std::fs::read(&path).map_err(|err| format!("{}: {}", path, err))?;
+    let end = text
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    parse_intcode_text(&text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_to_end(program: Vec<i64>, input: &[i64]) -> Result<Vec<i64>, EmulatorError<i64>> {
+        let mut emulator = Emulator::new(program);
+        emulator.extend_input(input.iter().cloned());
+        let mut outputs = Vec::new();
+        while let RunResult::Output(val) = emulator.run()? {
+            outputs.push(val);
+        }
+        Ok(outputs)
+    }
+
+    #[test]
+    fn test_run() {
+        let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+        assert_eq!(run_to_end(program.clone(), &[8]), Ok(vec![1]));
+        assert_eq!(run_to_end(program, &[7]), Ok(vec![0]));
+
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_eq!(run_to_end(quine.clone(), &[]), Ok(quine));
+    }
+
+    #[test]
+    fn test_invalid_opcode() {
+        assert_eq!(
+            run_to_end(vec![1101, 1, 2, 5, 42, 0], &[]),
+            Err(EmulatorError::InvalidOpcode {
+                instruction_pointer: 4,
+                instruction: 42,
+                opcode: 42,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_operand_mode() {
+        assert_eq!(
+            run_to_end(vec![1301, 0, 0, 0, 99], &[]),
+            Err(EmulatorError::InvalidOperandMode {
+                instruction_pointer: 0,
+                instruction: 1301,
+                mode: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_immediate_write() {
+        assert_eq!(
+            run_to_end(vec![3, 0, 10001, 0, 0, 5, 99], &[7]),
+            Err(EmulatorError::ImmediateWrite {
+                instruction_pointer: 2,
+                instruction: 10001,
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_is_repeatable() {
+        let mut emulator = Emulator::new(vec![104, 5, 42]);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(5)));
+        let err = emulator.run().unwrap_err();
+        assert_eq!(emulator.run().unwrap_err(), err);
+        assert_eq!(
+            err.to_string(),
+            "invalid opcode 42 in instruction 42 at address 2"
+        );
+    }
+
+    #[test]
+    fn test_run_with_budget() {
+        // jumps to itself forever
+        let mut emulator = Emulator::new(vec![1105, 1, 0]);
+        assert_eq!(emulator.run_with_budget(1000), Ok(RunResult::OutOfFuel));
+
+        // 1 + 2 + 3 + 4 + 99
+        let mut emulator = Emulator::new(vec![1101, 1, 2, 9, 1001, 9, 3, 9, 99, 0]);
+        assert_eq!(emulator.run_with_budget(0), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.run_with_budget(1), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.run_with_budget(1), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.run_with_budget(1), Ok(RunResult::Halt));
+        assert_eq!(emulator.read_memory(9), 6);
+    }
+
+    #[test]
+    fn test_run_with_budget_resumes_io() {
+        let mut emulator = Emulator::new(vec![3, 0, 4, 0, 104, 7, 99]);
+        assert_eq!(emulator.run_with_budget(5), Ok(RunResult::InputRequest));
+        emulator.push_input(3);
+        assert_eq!(emulator.run_with_budget(1), Ok(RunResult::Output(3)));
+        assert_eq!(emulator.run_with_budget(0), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.run(), Ok(RunResult::Output(7)));
+        assert_eq!(emulator.run_with_budget(1), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_sparse_memory() {
+        // writes 7 to 2^40, reads it back and outputs it
+        let far = 1i64 << 40;
+        let program = vec![1101, 3, 4, far, 4, far, 99];
+        let mut emulator = Emulator::new(program.clone());
+        assert_eq!(emulator.run(), Ok(RunResult::Output(7)));
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+
+        let mut emulator = Emulator::with_memory_limit(program, 1 << 20);
+        assert_eq!(
+            emulator.run(),
+            Err(EmulatorError::AddressOutOfRange {
+                instruction_pointer: 0,
+                address: far as Address,
+                limit: 1 << 20,
+            })
+        );
+    }
+
+    #[test]
+    fn test_memory_limit_instruction_pointer() {
+        let mut emulator = Emulator::with_memory_limit(vec![1105, 1, 50], 10);
+        assert_eq!(
+            emulator.run(),
+            Err(EmulatorError::AddressOutOfRange {
+                instruction_pointer: 50,
+                address: 50,
+                limit: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn test_into_memory_joins_pages() {
+        let mut emulator = Emulator::new(vec![1101, 3, 4, 2500, 99]);
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+        emulator.write_memory(PAGE_SIZE + 1, 5);
+        let memory = emulator.into_memory();
+        assert_eq!(memory.len(), 1);
+        let (start, words) = &memory[0];
+        assert_eq!(*start, 0);
+        assert_eq!(words.len(), 2501);
+        assert_eq!(&words[..5], &[1101, 3, 4, 2500, 99]);
+        assert_eq!(words[PAGE_SIZE + 1], 5);
+        assert_eq!(words[2500], 7);
+    }
+
+    #[test]
+    fn test_into_memory_after_sparse_write() {
+        let far = 1i64 << 40;
+        let mut emulator = Emulator::new(vec![1101, 3, 4, far, 99]);
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+        let mut image = vec![1101, 3, 4, far, 99];
+        image.resize(PAGE_SIZE, 0);
+        assert_eq!(
+            emulator.into_memory(),
+            vec![(0, image), (far as Address, vec![7])]
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let mut emulator = Emulator::new(vec![1101, 1, 2, 9, 109, 4, 104, 5, 99, 0]);
+        assert_eq!(emulator.step(), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.instruction_pointer(), 4);
+        assert_eq!(emulator.read_memory(9), 3);
+        assert_eq!(emulator.step(), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.relative_base_offset(), 4);
+        assert_eq!(emulator.step(), Ok(RunResult::Output(5)));
+        assert!(!emulator.is_halted());
+        assert_eq!(emulator.step(), Ok(RunResult::Halt));
+        assert!(emulator.is_halted());
+    }
+
+    #[test]
+    fn test_decode_cache_sees_self_modification() {
+        let program = vec![
+            104, 5, // output 5, then output [5] once rewritten to 4
+            1005, 16, 17, // halt on the second pass
+            1101, 0, 4, 0, // [0] = 4
+            1101, 0, 1, 16, // [16] = 1
+            1105, 1, 0, // jump to 0
+            0, 99,
+        ];
+        for &decode_cache in &[true, false] {
+            let mut emulator = Emulator::new(program.clone());
+            emulator.set_decode_cache(decode_cache);
+            assert_eq!(emulator.run_to_completion(None), Ok(vec![5, 1101]));
+        }
+
+        // input overwriting code that already ran
+        let mut emulator = Emulator::new(vec![104, 9, 3, 0, 1105, 1, 0]);
+        emulator.push_input(4);
+        let outputs = emulator.run_until_input().unwrap();
+        assert_eq!(outputs.values, vec![9, 0]);
+
+        let mut emulator = Emulator::new(vec![104, 9, 1105, 1, 0]);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(9)));
+        emulator.write_memory(0, 4);
+        emulator.write_memory(1, 2);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(1105)));
+    }
+
+    #[test]
+    fn test_negative_relative_base() {
+        let program = vec![
+            109, 30, // base 30
+            109, -10, // base 20
+            21101, 5, 6, -2, // [18] = 11
+            204, -2, // output [18]
+            109, -25, // base -5
+            204, 23, // output [18]
+            99,
+        ];
+        let mut emulator = Emulator::new(program);
+        assert_eq!(emulator.run_to_completion(None), Ok(vec![11, 11]));
+        assert_eq!(emulator.relative_base_offset(), -5);
+        assert_eq!(emulator.read_memory(18), 11);
+    }
+
+    #[test]
+    fn test_negative_address() {
+        let mut emulator = Emulator::new(vec![204, -1, 99]);
+        let error = emulator.run().unwrap_err();
+        assert_eq!(
+            error,
+            EmulatorError::NegativeAddress {
+                instruction_pointer: 0,
+                address: -1
+            }
+        );
+        assert_eq!(error.to_string(), "negative address -1 at address 0");
+
+        let mut emulator = Emulator::new(vec![109, -5, 203, 2, 99]);
+        emulator.push_input(1);
+        assert_eq!(
+            emulator.run(),
+            Err(EmulatorError::NegativeAddress {
+                instruction_pointer: 2,
+                address: -3
+            })
+        );
+        assert_eq!(emulator.pending_input(), 1);
+
+        for program in &[vec![4, -3, 99], vec![1105, 1, -7, 99]] {
+            assert!(matches!(
+                Emulator::new(program.clone()).run(),
+                Err(EmulatorError::NegativeAddress {
+                    instruction_pointer: 0,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // counts [20] down from 3, outputting each value
+        let program = vec![4, 20, 1001, 20, -1, 20, 1005, 20, 0, 99];
+        let mut emulator = Emulator::new(program);
+        emulator.write_memory(20, 3);
+        assert!(emulator.add_breakpoint(2));
+        assert_eq!(emulator.run(), Ok(RunResult::Output(3)));
+        assert_eq!(emulator.run(), Ok(RunResult::Breakpoint(2)));
+        assert_eq!(emulator.instruction_pointer(), 2);
+        assert_eq!(emulator.step(), Ok(RunResult::OutOfFuel));
+        assert_eq!(emulator.read_memory(20), 2);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(2)));
+        assert_eq!(emulator.run(), Ok(RunResult::Breakpoint(2)));
+        assert_eq!(emulator.run(), Ok(RunResult::Output(1)));
+        assert!(emulator.remove_breakpoint(2));
+        assert_eq!(emulator.breakpoints().count(), 0);
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_step_while_waiting_for_input() {
+        let mut emulator = Emulator::new(vec![3, 20, 4, 20, 99]);
+        emulator.add_breakpoint(2);
+        assert_eq!(emulator.run(), Ok(RunResult::InputRequest));
+        assert_eq!(emulator.step(), Ok(RunResult::InputRequest));
+        emulator.push_input(5);
+        assert_eq!(emulator.run(), Ok(RunResult::Breakpoint(2)));
+        assert_eq!(emulator.run(), Ok(RunResult::Output(5)));
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut emulator = Emulator::new(vec![3, 20, 1101, 1, 1, 21, 1101, 2, 2, 20, 99]);
+        emulator.add_watchpoint(20);
+        emulator.push_input(7);
+        assert_eq!(emulator.run(), Ok(RunResult::Watchpoint(20)));
+        assert_eq!(emulator.read_memory(20), 7);
+        assert_eq!(emulator.run(), Ok(RunResult::Watchpoint(20)));
+        assert_eq!(emulator.instruction_pointer(), 10);
+        assert_eq!(emulator.read_memory(20), 4);
+        assert_eq!(emulator.read_memory(21), 2);
+        assert_eq!(emulator.watchpoints().collect::<Vec<_>>(), vec![20]);
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_unbounded_input_queue() {
+        // sums 5000 inputs: loop { acc += input; counter -= 1 } until counter == 0
+        let program = vec![
+            1101, 0, 5000, 100, 3, 101, 1, 101, 102, 102, 1001, 100, -1, 100, 1005, 100, 4, 4, 102,
+            99,
+        ];
+        let mut emulator = Emulator::new(program);
+        emulator.extend_input(1..=5000);
+        assert_eq!(emulator.pending_input(), 5000);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(12_502_500)));
+        assert_eq!(emulator.pending_input(), 0);
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_bounded_input_queue() {
+        let mut emulator = Emulator::with_input_capacity(vec![3, 0, 99], 2);
+        assert_eq!(emulator.try_push_input(1), Ok(()));
+        assert_eq!(emulator.try_push_input(2), Ok(()));
+        assert_eq!(
+            emulator.try_push_input(3),
+            Err(EmulatorError::InputQueueFull {
+                capacity: 2,
+                input: 3
+            })
+        );
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+        assert_eq!(emulator.pending_input(), 1);
+        assert_eq!(emulator.try_push_input(3), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "input queue is full")]
+    fn test_bounded_input_queue_overflow_panics() {
+        let mut emulator = Emulator::with_input_capacity(vec![99], 1);
+        emulator.extend_input(vec![1, 2]);
+    }
+
+    #[test]
+    fn test_fork_is_independent() {
+        // keeps a running total far outside the image: loop { [5000] += input; output [5000] }
+        let program = vec![3, 100, 1, 100, 5000, 5000, 4, 5000, 1105, 1, 0];
+        let mut parent = Emulator::new(program.clone());
+        parent.push_input(10);
+        assert_eq!(parent.run(), Ok(RunResult::Output(10)));
+
+        let mut child = parent.fork();
+        child.push_input(1);
+        assert_eq!(child.run(), Ok(RunResult::Output(11)));
+        child.write_memory(7, 100);
+        child.write_memory(1 << 20, 5);
+        assert_eq!(parent.read_memory(100), 10);
+        assert_eq!(parent.read_memory(7), 5000);
+        assert_eq!(parent.read_memory(1 << 20), 0);
+        assert_eq!(parent.memory_len(), 5001);
+
+        parent.push_input(2);
+        assert_eq!(parent.run(), Ok(RunResult::Output(12)));
+        assert_eq!(child.read_memory(5000), 11);
+        assert_eq!(child.run(), Ok(RunResult::InputRequest));
+        assert_eq!(parent.run(), Ok(RunResult::InputRequest));
+        let mut expected = program;
+        expected[7] = 100;
+        assert_eq!(child.into_memory()[0].1[..expected.len()], expected[..]);
+    }
+
+    #[test]
+    fn test_fork_shares_pages_until_written() {
+        let mut parent = Emulator::new(vec![0; 3 * PAGE_SIZE]);
+        parent.write_memory(10 * PAGE_SIZE, 1);
+        let mut child = parent.fork();
+        let shared = |a: &Emulator<i64>, b: &Emulator<i64>| {
+            a.memory
+                .allocated_pages()
+                .zip(b.memory.allocated_pages())
+                .filter(|((_, a), (_, b))| std::ptr::eq(*a, *b))
+                .count()
+        };
+        assert_eq!(shared(&parent, &child), 4);
+        child.write_memory(PAGE_SIZE + 1, 7);
+        assert_eq!(shared(&parent, &child), 3);
+        parent.write_memory(10 * PAGE_SIZE, 2);
+        assert_eq!(shared(&parent, &child), 2);
+        assert_eq!(child.read_memory(10 * PAGE_SIZE), 1);
+        assert_eq!(parent.read_memory(PAGE_SIZE + 1), 0);
+    }
+
+    #[test]
+    fn test_outputs_iterator() {
+        let mut emulator = Emulator::new(vec![104, 1, 104, 2, 3, 9, 104, 3, 99, 0]);
+        let mut outputs = emulator.outputs();
+        assert_eq!(outputs.by_ref().collect::<Vec<_>>(), vec![Ok(1), Ok(2)]);
+        assert_eq!(outputs.stop(), Some(&RunResult::InputRequest));
+        assert_eq!(outputs.next(), None);
+
+        emulator.push_input(7);
+        let mut outputs = emulator.outputs();
+        assert_eq!(outputs.next(), Some(Ok(3)));
+        assert_eq!(outputs.next(), None);
+        assert_eq!(outputs.stop(), Some(&RunResult::Halt));
+
+        let mut emulator = Emulator::new(vec![104, 1, 42]);
+        let outputs = emulator.outputs().collect::<Vec<_>>();
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs[1].is_err());
+    }
+
+    #[test]
+    fn test_run_until_input() {
+        let mut emulator = Emulator::new(vec![104, 1, 104, 2, 3, 9, 104, 3, 99, 0]);
+        assert_eq!(
+            emulator.run_until_input(),
+            Ok(Outputs {
+                values: vec![1, 2],
+                stop: RunResult::InputRequest
+            })
+        );
+        emulator.push_input(7);
+        assert_eq!(
+            emulator.run_until_input(),
+            Ok(Outputs {
+                values: vec![3],
+                stop: RunResult::Halt
+            })
+        );
+        assert_eq!(emulator.read_memory(9), 7);
+    }
+
+    #[test]
+    fn test_run_to_completion() {
+        // outputs 999, 1000 or 1001 for an input below, equal to or above 8
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        for &(input, output) in &[(7, 999), (8, 1000), (9, 1001)] {
+            let mut emulator = Emulator::new(program.clone());
+            emulator.add_breakpoint(0);
+            assert_eq!(emulator.run_to_completion(vec![input]), Ok(vec![output]));
+            assert!(emulator.is_halted());
+        }
+
+        let mut emulator = Emulator::new(vec![3, 0, 4, 0, 3, 0, 99]);
+        assert_eq!(
+            emulator.run_to_completion(vec![5]),
+            Err(EmulatorError::InputExhausted {
+                instruction_pointer: 4
+            })
+        );
+
+        let mut emulator = Emulator::with_input_capacity(vec![3, 0, 4, 0, 99], 1);
+        assert_eq!(
+            emulator.run_to_completion(vec![5, 6]),
+            Err(EmulatorError::InputQueueFull {
+                capacity: 1,
+                input: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let program = vec![1002, 7, 3, 7, 4, 7, 99, i32::MAX / 2];
+        let mut emulator = Emulator::new(program.clone());
+        emulator.set_checked_arithmetic(true);
+        let error = emulator.run().unwrap_err();
+        assert_eq!(
+            error,
+            EmulatorError::ArithmeticOverflow {
+                instruction_pointer: 0,
+                operation: "mul",
+                lhs: i32::MAX / 2,
+                rhs: 3
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "overflow in mul of 1073741823 and 3 at address 0"
+        );
+        assert_eq!(emulator.instruction_pointer(), 0);
+
+        let mut emulator = Emulator::new(program.clone());
+        emulator.set_checked_arithmetic(true);
+        emulator.write_memory(7, 1000);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(3000)));
+
+        let mut emulator = Emulator::new(vec![1101, i32::MAX, 1, 5, 99, 0]);
+        emulator.set_checked_arithmetic(true);
+        assert!(matches!(
+            emulator.run(),
+            Err(EmulatorError::ArithmeticOverflow {
+                operation: "add",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_checked_relative_base() {
+        let mut emulator = Emulator::<i16>::new(vec![109, 20000, 204, 20000, 99]);
+        emulator.set_checked_arithmetic(true);
+        assert_eq!(
+            emulator.run(),
+            Err(EmulatorError::ArithmeticOverflow {
+                instruction_pointer: 2,
+                operation: "relative address",
+                lhs: 20000,
+                rhs: 20000
+            })
+        );
+    }
+
+    #[test]
+    fn test_big_int_words() {
+        use num::BigInt;
+        // squares 2^40 three times
+        let program = vec![
+            2,
+            15,
+            15,
+            15,
+            2,
+            15,
+            15,
+            15,
+            2,
+            15,
+            15,
+            15,
+            4,
+            15,
+            99,
+            1i64 << 40,
+        ]
+        .into_iter()
+        .map(BigInt::from)
+        .collect::<Vec<_>>();
+        let mut emulator = Emulator::new(program);
+        emulator.set_checked_arithmetic(true);
+        assert_eq!(
+            emulator.run_to_completion(None),
+            Ok(vec![BigInt::from(1) << 320])
+        );
+    }
+
+    #[test]
+    fn test_addresses_beyond_usize() {
+        use num::BigInt;
+        let far = 1i128 << 64;
+        for program in &[
+            vec![1101, 1, 2, far, 99],
+            vec![4, far, 99],
+            vec![1105, 1, far],
+        ] {
+            let mut emulator = Emulator::new(program.clone());
+            assert_eq!(
+                emulator.run(),
+                Err(EmulatorError::AddressOutOfRange {
+                    instruction_pointer: 0,
+                    address: Address::MAX,
+                    limit: Address::MAX,
+                })
+            );
+        }
+        let mut emulator = Emulator::new(vec![BigInt::from(4), BigInt::from(1) << 100]);
+        assert_eq!(
+            emulator.run(),
+            Err(EmulatorError::AddressOutOfRange {
+                instruction_pointer: 0,
+                address: Address::MAX,
+                limit: Address::MAX,
+            })
+        );
+    }
+
+    #[test]
+    fn test_opcodes_of_huge_and_negative_words() {
+        // output #7, with the instruction's unused digits past 2^64
+        let huge = 10i128.pow(20) + 104;
+        let mut emulator = Emulator::new(vec![huge, 7, 99]);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(7)));
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+
+        for &instruction in &[-1, -99, -104] {
+            assert_eq!(
+                run_to_end(vec![instruction, 0, 0], &[]),
+                Err(EmulatorError::InvalidOpcode {
+                    instruction_pointer: 0,
+                    instruction,
+                    opcode: instruction % 100,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrapping_words() {
+        use std::num::Wrapping;
+        let program = vec![1002, 7, 2, 7, 4, 7, 99, i64::MAX]
+            .into_iter()
+            .map(Wrapping)
+            .collect::<Vec<_>>();
+        let mut emulator = Emulator::new(program.clone());
+        assert_eq!(emulator.run(), Ok(RunResult::Output(Wrapping(-2))));
+
+        let mut emulator = Emulator::new(program);
+        emulator.set_checked_arithmetic(true);
+        assert!(matches!(
+            emulator.run(),
+            Err(EmulatorError::ArithmeticOverflow { .. })
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+//! Talking to intcode programs that speak ASCII.
+//!
+//! Such programs read and write one character per word, in newline-terminated lines. Anything
+//! outside the ASCII range, usually a final answer, is passed through as a plain value.
+
+use super::{Emulator, EmulatorError, IntcodeWord, RunResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiOutput<Word> {
+    /// A line of text, without its newline.
+    Line(String),
+    /// An output word that isn't an ASCII character.
+    Value(Word),
+}
+
+/// Wraps an `Emulator`, turning its input and output into lines of text.
+#[derive(Debug)]
+pub struct AsciiEmulator<Word> {
+    emulator: Emulator<Word>,
+    line: String,
+    pending_value: Option<Word>,
+    stop: Option<RunResult<Word>>,
+}
+
+impl<Word> AsciiEmulator<Word>
+where
+    Word: IntcodeWord,
+{
+    pub fn new(emulator: Emulator<Word>) -> Self {
+        Self {
+            emulator,
+            line: String::new(),
+            pending_value: None,
+            stop: None,
+        }
+    }
+
+    /// Queues `line` followed by a newline as input.
+    ///
+    /// Panics if `line` contains anything but ASCII characters.
+    pub fn push_line(&mut self, line: &str) {
+        assert!(line.is_ascii(), "input line is not ASCII: {:?}", line);
+        let bytes = line.bytes().chain(Some(b'\n'));
+        self.emulator
+            .extend_input(bytes.map(|byte| Word::from_u8(byte).unwrap()));
+    }
+
+    /// Runs until the next complete line or non-ASCII value.
+    ///
+    /// Returns `None` once the program stops, after flushing any partial line it printed, such
+    /// as a prompt without a newline. `stop` then tells whether it halted, waits for input or
+    /// hit a breakpoint or watchpoint.
+    pub fn next_output(&mut self) -> Result<Option<AsciiOutput<Word>>, EmulatorError<Word>> {
+        self.stop = None;
+        if let Some(value) = self.pending_value.take() {
+            return Ok(Some(AsciiOutput::Value(value)));
+        }
+        loop {
+            let value = match self.emulator.run()? {
+                RunResult::Output(value) => value,
+                stop => {
+                    self.stop = Some(stop);
+                    return Ok(self.take_line());
+                }
+            };
+            match value.to_u8().filter(u8::is_ascii) {
+                Some(b'\n') => return Ok(Some(AsciiOutput::Line(self.take_line_text()))),
+                Some(byte) => self.line.push(byte as char),
+                None if self.line.is_empty() => return Ok(Some(AsciiOutput::Value(value))),
+                None => {
+                    self.pending_value = Some(value);
+                    return Ok(self.take_line());
+                }
+            }
+        }
+    }
+
+    /// Iterates over `next_output` until the program stops.
+    pub fn outputs(
+        &mut self,
+    ) -> impl Iterator<Item = Result<AsciiOutput<Word>, EmulatorError<Word>>> + '_ {
+        std::iter::from_fn(move || self.next_output().transpose())
+    }
+
+    /// Runs until the program stops, returning just the text it printed.
+    ///
+    /// Non-ASCII values are dropped.
+    pub fn read_text(&mut self) -> Result<Vec<String>, EmulatorError<Word>> {
+        self.outputs()
+            .filter_map(|output| match output {
+                Ok(AsciiOutput::Line(line)) => Some(Ok(line)),
+                Ok(AsciiOutput::Value(_)) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+
+    fn take_line_text(&mut self) -> String {
+        std::mem::take(&mut self.line)
+    }
+
+    fn take_line(&mut self) -> Option<AsciiOutput<Word>> {
+        if self.line.is_empty() {
+            None
+        } else {
+            Some(AsciiOutput::Line(self.take_line_text()))
+        }
+    }
+
+    /// What stopped the program the last time `next_output` ran it: `RunResult::Halt`,
+    /// `RunResult::InputRequest`, or a breakpoint or watchpoint. `None` while it is still
+    /// producing output or if it failed.
+    pub fn stop(&self) -> Option<&RunResult<Word>> {
+        self.stop.as_ref()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.emulator.is_halted()
+    }
+
+    pub fn emulator_mut(&mut self) -> &mut Emulator<Word> {
+        &mut self.emulator
+    }
+
+    pub fn into_inner(self) -> Emulator<Word> {
+        self.emulator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::assembler::assemble;
+
+    fn ascii_emulator(source: &str) -> AsciiEmulator<i64> {
+        AsciiEmulator::new(Emulator::new(assemble(source).unwrap()))
+    }
+
+    #[test]
+    fn test_echo_line() {
+        // prompts, echoes one line of input and reports its length as a non-ASCII value
+        let mut ascii = ascii_emulator(
+            "
+            output #62
+            output #10
+            loop:   input [char]
+                    output [char]
+                    add [count], #1, [count]
+                    equals [char], #10, [done]
+                    jump_if_false [done], #loop
+                    add [count], #999, [count]
+                    output [count]
+                    halt
+            char:   data 0
+            count:  data 0
+            done:   data 0
+            ",
+        );
+        assert_eq!(ascii.read_text(), Ok(vec![">".to_owned()]));
+        assert_eq!(ascii.stop(), Some(&RunResult::InputRequest));
+
+        ascii.push_line("hello");
+        let outputs = ascii.outputs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            outputs,
+            vec![
+                AsciiOutput::Line("hello".to_owned()),
+                AsciiOutput::Value(1005)
+            ]
+        );
+        assert!(ascii.is_halted());
+        assert_eq!(ascii.stop(), Some(&RunResult::Halt));
+    }
+
+    #[test]
+    fn test_stop_at_breakpoint() {
+        let mut ascii = ascii_emulator("output #97\noutput #10\noutput #98\nhalt");
+        ascii.emulator_mut().add_breakpoint(4);
+        assert_eq!(
+            ascii.next_output(),
+            Ok(Some(AsciiOutput::Line("a".to_owned())))
+        );
+        assert_eq!(ascii.stop(), None);
+        assert_eq!(ascii.next_output(), Ok(None));
+        assert_eq!(ascii.stop(), Some(&RunResult::Breakpoint(4)));
+        assert!(!ascii.is_halted());
+        assert_eq!(ascii.read_text(), Ok(vec!["b".to_owned()]));
+        assert_eq!(ascii.stop(), Some(&RunResult::Halt));
+    }
+
+    #[test]
+    fn test_values_split_lines() {
+        let mut ascii = ascii_emulator(
+            "
+            output #97
+            output #-1
+            output #98
+            output #10
+            output #200
+            output #99
+            halt
+            ",
+        );
+        let outputs = ascii.outputs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            outputs,
+            vec![
+                AsciiOutput::Line("a".to_owned()),
+                AsciiOutput::Value(-1),
+                AsciiOutput::Line("b".to_owned()),
+                AsciiOutput::Value(200),
+                AsciiOutput::Line("c".to_owned()),
+            ]
+        );
+        assert_eq!(ascii.next_output(), Ok(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "not ASCII")]
+    fn test_non_ascii_input_panics() {
+        ascii_emulator("halt").push_line("héllo");
+    }
+}
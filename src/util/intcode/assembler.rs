@@ -0,0 +1,357 @@
+//! A small assembly language for intcode.
+//!
+//! ```text
+//! ; comments run to the end of the line
+//! start:  input [counter]           ; position mode
+//!         add #1, rb-2, rb+3        ; immediate and relative mode
+//!         jump_if_true #1, #start   ; labels are plain addresses
+//! 9:      halt                      ; a numeric label asserts the current address
+//! counter: data 0, 1, start+2
+//! ```
+//!
+//! Mnemonics are the names from the emulator's instruction table, so anything printed by the
+//! disassembler assembles back into the same program. That includes writes to an immediate
+//! operand, which the disassembler lists when data happens to decode that way; the emulator
+//! only rejects them when they are executed.
+
+use super::{Address, Emulator, IntcodeWord, OperandMode};
+use crate::util::parsers::signed_number;
+use nom::combinator::all_consuming;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Zero};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssemblyError {}
+
+#[derive(Debug, Clone)]
+enum Expr<Word> {
+    Literal(Word),
+    Label { name: String, offset: Word },
+}
+
+#[derive(Debug, Clone)]
+enum Statement<Word> {
+    Instruction {
+        opcode: usize,
+        operands: Vec<(OperandMode, Expr<Word>)>,
+    },
+    Data(Vec<Expr<Word>>),
+}
+
+impl<Word> Statement<Word> {
+    fn size(&self) -> usize {
+        match self {
+            Statement::Instruction { operands, .. } => 1 + operands.len(),
+            Statement::Data(values) => values.len(),
+        }
+    }
+}
+
+fn is_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_number<Word>(text: &str) -> Result<Word, String>
+where
+    Word: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+{
+    all_consuming(signed_number::<Word>)(text.trim().as_bytes())
+        .map(|(_, value)| value)
+        .map_err(|_| format!("invalid number `{}`", text.trim()))
+}
+
+fn parse_expr<Word>(text: &str) -> Result<Expr<Word>, String>
+where
+    Word: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+{
+    let text = text.trim();
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return parse_number(text).map(Expr::Literal);
+    }
+    let (name, offset) = match text.find(['+', '-']) {
+        Some(split) => (&text[..split], parse_number(&text[split..])?),
+        None => (text, Word::zero()),
+    };
+    let name = name.trim();
+    if !is_label_name(name) {
+        return Err(format!("invalid label `{}`", name));
+    }
+    Ok(Expr::Label {
+        name: name.to_owned(),
+        offset,
+    })
+}
+
+fn parse_operand<Word>(text: &str) -> Result<(OperandMode, Expr<Word>), String>
+where
+    Word: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+{
+    let text = text.trim();
+    if let Some(expr) = text.strip_prefix('#') {
+        Ok((OperandMode::Immediate, parse_expr(expr)?))
+    } else if let Some(expr) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        Ok((OperandMode::Position, parse_expr(expr)?))
+    } else if text == "rb" {
+        Ok((OperandMode::Relative, Expr::Literal(Word::zero())))
+    } else if let Some(expr) = text.strip_prefix("rb+") {
+        Ok((OperandMode::Relative, parse_expr(expr)?))
+    } else if let Some(expr) = text.strip_prefix("rb-") {
+        match parse_expr(expr)? {
+            Expr::Literal(value) => Word::zero()
+                .checked_sub(&value)
+                .map(|value| (OperandMode::Relative, Expr::Literal(value)))
+                .ok_or_else(|| format!("value out of range in `{}`", text)),
+            Expr::Label { .. } => Err(format!("cannot negate a label in `{}`", text)),
+        }
+    } else {
+        Err(format!(
+            "invalid operand `{}`, expected `[x]`, `#x` or `rb+x`",
+            text
+        ))
+    }
+}
+
+fn parse_list<T>(text: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    text.split(',').map(parse).collect()
+}
+
+/// Assembles source text into an intcode image that can be passed to `Emulator::new`.
+pub fn assemble<Word>(source: &str) -> Result<Vec<Word>, AssemblyError>
+where
+    Word: IntcodeWord + CheckedAdd + CheckedSub + CheckedMul,
+{
+    let mut labels = HashMap::<String, Address>::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| AssemblyError {
+            line: line_number,
+            message,
+        };
+        let mut rest = line.split(';').next().unwrap().trim();
+
+        while let Some(colon) = rest.find(':') {
+            let label = rest[..colon].trim();
+            if let Ok(expected) = label.parse::<Address>() {
+                if expected != address {
+                    return Err(error(format!(
+                        "address mismatch: label says {}, but this is address {}",
+                        expected, address
+                    )));
+                }
+            } else if !is_label_name(label) {
+                break;
+            } else if labels.insert(label.to_owned(), address).is_some() {
+                return Err(error(format!("duplicate label `{}`", label)));
+            }
+            rest = rest[colon + 1..].trim();
+        }
+        if rest.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, arguments) = match rest.find(char::is_whitespace) {
+            Some(split) => (&rest[..split], &rest[split..]),
+            None => (rest, ""),
+        };
+        let statement = if mnemonic == "data" {
+            Statement::Data(parse_list(arguments, parse_expr).map_err(error)?)
+        } else {
+            let info = Emulator::<Word>::INSTRUCTIONS
+                .iter()
+                .find(|info| info.mnemonic == mnemonic)
+                .ok_or_else(|| error(format!("unknown mnemonic `{}`", mnemonic)))?;
+            let operands = parse_list(arguments, parse_operand).map_err(error)?;
+            if operands.len() != info.operands.len() {
+                return Err(error(format!(
+                    "`{}` takes {} operands, found {}",
+                    mnemonic,
+                    info.operands.len(),
+                    operands.len()
+                )));
+            }
+            Statement::Instruction {
+                opcode: info.opcode as usize,
+                operands,
+            }
+        };
+        address += statement.size();
+        statements.push((line_number, statement));
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for (line_number, statement) in statements {
+        let error = |message: String| AssemblyError {
+            line: line_number,
+            message,
+        };
+        let resolve = |expr: &Expr<Word>| match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Label { name, offset } => labels
+                .get(name)
+                .ok_or_else(|| format!("undefined label `{}`", name))
+                .and_then(|&address| {
+                    Word::from_usize(address)
+                        .and_then(|address| IntcodeWord::checked_add(&address, offset))
+                        .ok_or_else(|| format!("address of `{}` is out of range", name))
+                })
+                .map_err(error),
+        };
+        match statement {
+            Statement::Instruction { opcode, operands } => {
+                let mut instruction = opcode;
+                let mut multiplier = 100;
+                for (mode, _) in &operands {
+                    instruction += multiplier * mode.digit();
+                    multiplier *= 10;
+                }
+                program.push(Word::from_usize(instruction).ok_or_else(|| {
+                    error(format!("instruction {} is out of range", instruction))
+                })?);
+                for (_, expr) in &operands {
+                    program.push(resolve(expr)?);
+                }
+            }
+            Statement::Data(values) => {
+                for expr in &values {
+                    program.push(resolve(expr)?);
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::disassembler::disassemble;
+    use crate::util::intcode::{parse_intcode_text, RunResult};
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble::<i64>(
+            "
+            add [9], [10], [3]  ; day 2 example
+            mul [3], [11], [0]
+            halt
+            data 30, 40, 50
+            ",
+        )
+        .unwrap();
+        assert_eq!(program, vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let program = assemble::<i64>(
+            "
+            start: input [value]
+                   jump_if_false [value], #end
+                   output [value]
+                   jump_if_true #1, #start
+            end:   halt
+            value: data 0
+            table: data value, end-1, table+1
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![3, 11, 1006, 11, 10, 4, 11, 1105, 1, 0, 99, 0, 11, 9, 13]
+        );
+
+        let mut emulator = Emulator::new(program);
+        emulator.extend_input(vec![4, 2, 0]);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(4)));
+        assert_eq!(emulator.run(), Ok(RunResult::Output(2)));
+        assert_eq!(emulator.run(), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_relative_operands() {
+        let program =
+            assemble::<i64>("add_to_relative_base #10\nadd rb-1, rb, rb+2\nhalt").unwrap();
+        assert_eq!(program, vec![109, 10, 22201, -1, 0, 2, 99]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let listing = disassemble(&program).to_string();
+        assert_eq!(assemble::<i64>(&listing), Ok(program));
+    }
+
+    #[test]
+    fn test_round_trip_puzzles() {
+        for input in &[
+            include_str!("../../../input/2019/day9.txt"),
+            include_str!("../../../input/2019/day13.txt"),
+            include_str!("../../../input/2019/day15.txt"),
+        ] {
+            let program = parse_intcode_text::<i64>(input.trim().as_bytes()).unwrap();
+            let listing = disassemble(&program).to_string();
+            assert_eq!(assemble::<i64>(&listing), Ok(program));
+        }
+    }
+
+    #[test]
+    fn test_immediate_writes() {
+        let program = vec![11101, 1, 2, 3, 103, 3, 99];
+        assert_eq!(
+            assemble::<i64>("add #1, #2, #3\ninput #3\nhalt"),
+            Ok(program.clone())
+        );
+        let listing = disassemble(&program).to_string();
+        assert_eq!(assemble::<i64>(&listing), Ok(program));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| assemble::<i64>(source).unwrap_err().to_string();
+        assert_eq!(error("halt\nfoo #1"), "line 2: unknown mnemonic `foo`");
+        assert_eq!(
+            error("add #1, #2"),
+            "line 1: `add` takes 3 operands, found 2"
+        );
+        assert_eq!(
+            error("output [nowhere]"),
+            "line 1: undefined label `nowhere`"
+        );
+        assert_eq!(error("a: halt\na: halt"), "line 2: duplicate label `a`");
+        assert_eq!(
+            error("halt\n0: halt"),
+            "line 2: address mismatch: label says 0, but this is address 1"
+        );
+        assert_eq!(
+            error("output 5"),
+            "line 1: invalid operand `5`, expected `[x]`, `#x` or `rb+x`"
+        );
+        assert_eq!(error("data 1, 2x"), "line 1: invalid number `2x`");
+        assert_eq!(error("data a.b"), "line 1: invalid label `a.b`");
+    }
+}
@@ -0,0 +1,330 @@
+//! Control flow graph recovery for intcode images.
+//!
+//! Code is found the way `disassemble` finds it: by following control flow from address 0
+//! through fall-throughs and jumps with an immediate target. A jump whose target is read from
+//! memory or relative to the relative base can't be followed, so its block is flagged instead.
+//!
+//! Intcode programs return from functions with such jumps, which would leave every return
+//! site unreachable. To recover them, any immediate operand that points at a decodable
+//! instruction outside the code found so far is treated as a possible target of an indirect
+//! jump; these blocks are marked as having their address taken but get no incoming edges.
+
+use super::disassembler::{decode_at, flow_of, Line};
+use super::{Address, IntcodeWord, OperandKind, OperandMode};
+use petgraph::{dot::Dot, graph::NodeIndex, Graph};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// The block ends because the next one starts at a jump target.
+    Next,
+    Taken,
+    NotTaken,
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edge::Next => Ok(()),
+            Edge::Taken => write!(f, "taken"),
+            Edge::NotTaken => write!(f, "not taken"),
+        }
+    }
+}
+
+/// A run of instructions that is only entered at its first one and only left after its last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock<Word> {
+    /// Always `Line::Instruction`s, in address order.
+    pub instructions: Vec<Line<Word>>,
+    /// The block ends in a jump whose target is only known at run time.
+    pub indirect_jump: bool,
+    /// The block's address appears as an immediate operand, so an indirect jump may lead here.
+    pub address_taken: bool,
+}
+
+impl<Word> BasicBlock<Word> {
+    pub fn start(&self) -> Address {
+        self.instructions[0].address()
+    }
+
+    /// The address just past the block's last instruction.
+    pub fn end(&self) -> Address {
+        match self.instructions.last() {
+            Some(Line::Instruction {
+                address, decoded, ..
+            }) => address + decoded.info.size(),
+            _ => self.start(),
+        }
+    }
+}
+
+impl<Word> fmt::Display for BasicBlock<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.address_taken {
+            writeln!(f, "(address taken)")?;
+        }
+        for (i, line) in self.instructions.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        if self.indirect_jump {
+            write!(f, "\n(indirect jump)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph<Word> {
+    pub graph: Graph<BasicBlock<Word>, Edge>,
+    /// The block starting at address 0.
+    pub entry: NodeIndex,
+    starts: HashMap<Address, NodeIndex>,
+}
+
+impl<Word> ControlFlowGraph<Word> {
+    /// The block starting at `address`, if there is one.
+    pub fn block_at(&self, address: Address) -> Option<NodeIndex> {
+        self.starts.get(&address).copied()
+    }
+}
+
+impl<Word> ControlFlowGraph<Word>
+where
+    Word: fmt::Display,
+{
+    /// Renders the graph in Graphviz DOT, with each block labelled by its disassembly.
+    pub fn to_dot(&self) -> String {
+        format!("{:#}", Dot::new(&self.graph))
+    }
+}
+
+/// Recovers the basic blocks of `program` and the jumps between them.
+///
+/// Returns `None` if no instruction can be decoded at address 0, as in an empty program or an
+/// image that starts with data.
+pub fn control_flow_graph<Word>(program: &[Word]) -> Option<ControlFlowGraph<Word>>
+where
+    Word: IntcodeWord,
+{
+    let mut instructions = BTreeMap::new();
+    let mut covered = vec![false; program.len()];
+    let mut leaders = BTreeSet::new();
+    let mut address_taken = BTreeSet::new();
+    leaders.insert(0);
+
+    let mut worklist = vec![0];
+    loop {
+        while let Some(address) = worklist.pop() {
+            if address >= program.len() || covered[address] {
+                continue;
+            }
+            let (decoded, operands) = match decode_at(program, address) {
+                Some(instruction) => instruction,
+                None => continue,
+            };
+            let end = address + decoded.info.size();
+            if covered[address..end].iter().any(|&c| c) {
+                continue;
+            }
+            covered[address..end].iter_mut().for_each(|c| *c = true);
+
+            let flow = flow_of(&decoded, &operands);
+            if flow.falls_through {
+                worklist.push(end);
+            }
+            if let Some(target) = flow.jump_target {
+                leaders.insert(target);
+                worklist.push(target);
+            }
+            instructions.insert(address, (decoded, operands));
+        }
+
+        for (decoded, operands) in instructions.values() {
+            if decoded.info.opcode.is_jump() {
+                continue;
+            }
+            let immediates = decoded
+                .info
+                .operands
+                .iter()
+                .zip(decoded.operand_modes())
+                .zip(operands)
+                .filter(|((&kind, &mode), _)| {
+                    kind == OperandKind::Read && mode == OperandMode::Immediate
+                })
+                .filter_map(|(_, value)| value.to_address());
+            for target in immediates {
+                if target < program.len() && !covered[target] && address_taken.insert(target) {
+                    leaders.insert(target);
+                    worklist.push(target);
+                }
+            }
+        }
+        if worklist.is_empty() {
+            break;
+        }
+    }
+
+    let mut blocks: Vec<BasicBlock<Word>> = Vec::new();
+    let mut previous_end = None;
+    for (&address, (decoded, operands)) in &instructions {
+        let starts_block = leaders.contains(&address) || previous_end != Some(address);
+        if starts_block {
+            blocks.push(BasicBlock {
+                instructions: Vec::new(),
+                indirect_jump: false,
+                address_taken: address_taken.contains(&address),
+            });
+        }
+        let block = blocks.last_mut().unwrap();
+        block.instructions.push(Line::Instruction {
+            address,
+            decoded: *decoded,
+            operands: operands.clone(),
+        });
+        let flow = flow_of(decoded, operands);
+        let ends_block = decoded.info.opcode.is_jump() || !flow.falls_through;
+        block.indirect_jump = flow.indirect_jump;
+        previous_end = if ends_block {
+            None
+        } else {
+            Some(address + decoded.info.size())
+        };
+    }
+
+    let mut graph = Graph::new();
+    let starts: HashMap<_, _> = blocks
+        .into_iter()
+        .map(|block| (block.start(), graph.add_node(block)))
+        .collect();
+    let mut edges = Vec::new();
+    for &node in starts.values() {
+        let block = &graph[node];
+        let (decoded, operands) = match block.instructions.last() {
+            Some(Line::Instruction {
+                decoded, operands, ..
+            }) => (decoded, operands),
+            _ => continue,
+        };
+        let flow = flow_of(decoded, operands);
+        let is_jump = decoded.info.opcode.is_jump();
+        if let Some(&target) = flow.jump_target.and_then(|target| starts.get(&target)) {
+            edges.push((node, target, Edge::Taken));
+        }
+        if let Some(&next) = starts.get(&block.end()).filter(|_| flow.falls_through) {
+            edges.push((
+                node,
+                next,
+                if is_jump { Edge::NotTaken } else { Edge::Next },
+            ));
+        }
+    }
+    edges.sort_by_key(|&(from, to, _)| (from, to));
+    for (from, to, edge) in edges {
+        graph.add_edge(from, to, edge);
+    }
+
+    let entry = *starts.get(&0)?;
+    Some(ControlFlowGraph {
+        graph,
+        entry,
+        starts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls a function that works on its argument in a loop, outputs the result and halts.
+    /// The function returns through the address the caller stored at `rb+0`.
+    const CALL: [i64; 28] = [
+        109, 100, // 0: add_to_relative_base #100
+        21101, 9, 0, 0, // 2: add #9, #0, rb+0
+        1105, 1, 13, // 6: jump_if_true #1, #13
+        4, 27, // 9: output [27]
+        99, // 11: halt
+        0,  // 12: data
+        1002, 27, 2, 27, // 13: mul [27], #2, [27]
+        1001, 27, -1, 27, // 17: add [27], #-1, [27]
+        1006, 27, 17, // 21: jump_if_false [27], #17
+        2105, 1, 0, // 24: jump_if_true #1, rb+0
+        5, // 27: data
+    ];
+
+    fn starts(cfg: &ControlFlowGraph<i64>) -> Vec<Address> {
+        let mut starts: Vec<_> = cfg
+            .graph
+            .raw_nodes()
+            .iter()
+            .map(|node| node.weight.start())
+            .collect();
+        starts.sort();
+        starts
+    }
+
+    fn edge(cfg: &ControlFlowGraph<i64>, from: Address, to: Address) -> Option<Edge> {
+        let from = cfg.block_at(from)?;
+        let to = cfg.block_at(to)?;
+        cfg.graph.find_edge(from, to).map(|edge| cfg.graph[edge])
+    }
+
+    #[test]
+    fn test_blocks() {
+        let cfg = control_flow_graph(&[1101, 1, 2, 5, 99, 0]).unwrap();
+        assert_eq!(cfg.graph.node_count(), 1);
+        assert_eq!(cfg.graph.edge_count(), 0);
+        let block = &cfg.graph[cfg.entry];
+        assert_eq!((block.start(), block.end()), (0, 5));
+        assert!(!block.indirect_jump && !block.address_taken);
+    }
+
+    #[test]
+    fn test_no_entry() {
+        assert!(control_flow_graph::<i64>(&[]).is_none());
+        assert!(control_flow_graph(&[0, 1101, 1, 2, 0, 99]).is_none());
+        assert!(control_flow_graph(&[1101, 1, 2]).is_none());
+    }
+
+    #[test]
+    fn test_edges() {
+        let cfg = control_flow_graph(&CALL).unwrap();
+        assert_eq!(starts(&cfg), vec![0, 9, 13, 17, 24]);
+        assert_eq!(edge(&cfg, 0, 13), Some(Edge::Taken));
+        assert_eq!(edge(&cfg, 0, 9), None);
+        assert_eq!(edge(&cfg, 13, 17), Some(Edge::Next));
+        assert_eq!(edge(&cfg, 17, 17), Some(Edge::Taken));
+        assert_eq!(edge(&cfg, 17, 24), Some(Edge::NotTaken));
+        assert_eq!(cfg.graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_indirect_jumps() {
+        let cfg = control_flow_graph(&CALL).unwrap();
+        let flagged = |address| {
+            let block = &cfg.graph[cfg.block_at(address).unwrap()];
+            (block.indirect_jump, block.address_taken)
+        };
+        assert_eq!(flagged(0), (false, false));
+        assert_eq!(flagged(9), (false, true));
+        assert_eq!(flagged(17), (false, false));
+        assert_eq!(flagged(24), (true, false));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = control_flow_graph(&CALL).unwrap().to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains(r#"(address taken)\l     9: output [27]\l    11: halt\l"#));
+        assert!(dot.contains(r#"jump_if_true #1, rb+0\l(indirect jump)\l"#));
+        assert!(dot.contains(r#"[label="not taken\l"]"#));
+    }
+}
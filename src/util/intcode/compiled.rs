@@ -0,0 +1,521 @@
+//! A second execution tier that compiles basic blocks of a program into a compact bytecode.
+//!
+//! Compiling resolves opcodes, operand modes and position addresses once per block, so running
+//! compiled code skips decoding entirely. A block ends with the first jump, input, output or
+//! halt, or right before an instruction the compiler leaves to the interpreter: one that
+//! doesn't decode, writes to an immediate operand, uses a negative address or lies past the
+//! program image. Those errors still come from the interpreter, with the same details.
+//!
+//! Compiled code only runs while nothing needs to see single instructions: without a fuel
+//! budget, breakpoints, watchpoints, a trace or a profile. When the program writes to a word of
+//! compiled code, only the blocks containing that word are recompiled. They now end before the
+//! instruction with the written word, which the interpreter runs from then on, so code that
+//! patches its own operands still runs mostly compiled.
+
+use super::{
+    Address, Emulator, EmulatorError, IntcodeWord, Opcode, OperandKind, OperandMode, State,
+};
+use std::ops::{Add, Mul};
+use std::sync::Arc;
+
+/// How an `Emulator` executes a program, see `Emulator::set_tier`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Tier {
+    /// Decodes and executes one instruction at a time.
+    #[default]
+    Interpreter,
+    /// Runs compiled basic blocks, falling back to the interpreter where needed.
+    Compiled,
+}
+
+#[derive(Debug, Clone)]
+enum Operand<Word> {
+    Immediate(Word),
+    Position(Address),
+    Relative(Word),
+}
+
+#[derive(Debug, Clone)]
+enum Op<Word> {
+    Add(Operand<Word>, Operand<Word>, Operand<Word>),
+    Mul(Operand<Word>, Operand<Word>, Operand<Word>),
+    Input(Operand<Word>),
+    Output(Operand<Word>),
+    JumpIfTrue(Operand<Word>, Operand<Word>),
+    JumpIfFalse(Operand<Word>, Operand<Word>),
+    LessThan(Operand<Word>, Operand<Word>, Operand<Word>),
+    Equals(Operand<Word>, Operand<Word>, Operand<Word>),
+    AddToRelativeBase(Operand<Word>),
+    Halt,
+}
+
+impl<Word> Op<Word> {
+    fn ends_block(&self) -> bool {
+        !matches!(
+            self,
+            Op::Add(..)
+                | Op::Mul(..)
+                | Op::LessThan(..)
+                | Op::Equals(..)
+                | Op::AddToRelativeBase(..)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Instruction<Word> {
+    address: Address,
+    next: Address,
+    op: Op<Word>,
+}
+
+type Block<Word> = Arc<[Instruction<Word>]>;
+
+/// Compiled blocks by start address.
+///
+/// Blocks that compiled to nothing are kept too, so the compiler doesn't retry every time the
+/// interpreter has to run the instruction there.
+#[derive(Debug, Clone)]
+pub(super) struct Blocks<Word> {
+    /// Blocks compiled up front, shared between forks.
+    shared: Arc<Vec<Option<Block<Word>>>>,
+    /// Blocks compiled while running, which take precedence over shared ones.
+    local: Vec<Option<Block<Word>>>,
+    /// Words the program wrote to after they were compiled.
+    volatile: Vec<bool>,
+}
+
+impl<Word> Default for Blocks<Word> {
+    fn default() -> Self {
+        Self {
+            shared: Arc::default(),
+            local: Vec::new(),
+            volatile: Vec::new(),
+        }
+    }
+}
+
+impl<Word> Blocks<Word> {
+    fn get(&self, start: Address) -> Option<&Block<Word>> {
+        let local = self.local.get(start).and_then(Option::as_ref);
+        local.or_else(|| self.shared.get(start).and_then(Option::as_ref))
+    }
+
+    /// Start addresses of all blocks with instructions in `words`.
+    fn covering(&self, words: &[Address]) -> Vec<Address> {
+        let covers = |block: &Block<Word>| match (block.first(), block.last()) {
+            (Some(first), Some(last)) => {
+                let range = first.address..last.next;
+                words.iter().any(|word| range.contains(word))
+            }
+            _ => false,
+        };
+        let starts = |blocks: &[Option<Block<Word>>]| {
+            (0..blocks.len())
+                .filter(|&start| matches!(&blocks[start], Some(block) if covers(block)))
+                .collect::<Vec<_>>()
+        };
+        let mut starts = [starts(&self.shared), starts(&self.local)].concat();
+        starts.sort_unstable();
+        starts.dedup();
+        starts
+    }
+}
+
+fn insert<Word>(blocks: &mut Vec<Option<Block<Word>>>, start: Address, block: Block<Word>) {
+    if blocks.len() <= start {
+        blocks.resize(start + 1, None);
+    }
+    blocks[start] = Some(block);
+}
+
+fn compile_operand<Word>(word: &Word, mode: OperandMode, kind: OperandKind) -> Option<Operand<Word>>
+where
+    Word: IntcodeWord,
+{
+    match (mode, kind) {
+        (OperandMode::Immediate, OperandKind::Read) => Some(Operand::Immediate(word.clone())),
+        (OperandMode::Immediate, OperandKind::Write) => None,
+        (OperandMode::Position, _) => word.to_address().map(Operand::Position),
+        (OperandMode::Relative, _) => Some(Operand::Relative(word.clone())),
+    }
+}
+
+impl<Word> Emulator<Word>
+where
+    Word: IntcodeWord,
+{
+    /// Chooses between the interpreter, the default, and compiled code.
+    ///
+    /// Both tiers produce the same outputs, errors and machine state.
+    pub fn set_tier(&mut self, tier: Tier) {
+        self.tier = tier;
+        match tier {
+            Tier::Interpreter => self.drop_compiled_code(),
+            Tier::Compiled => self.precompile(),
+        }
+    }
+
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+
+    pub(super) fn can_run_compiled(&self) -> bool {
+        self.tier == Tier::Compiled
+            && self.breakpoints.is_empty()
+            && self.watchpoints.is_empty()
+            && self.tracer.is_none()
+            && self.profile.is_none()
+    }
+
+    pub(super) fn drop_compiled_code(&mut self) {
+        self.blocks = Blocks::default();
+        self.memory.code = Arc::default();
+        self.memory.modified_code.clear();
+    }
+
+    /// Marks words written to since the last call so they are never compiled again, and
+    /// recompiles the blocks containing them.
+    fn recompile_modified_code(&mut self) {
+        if self.memory.modified_code.is_empty() {
+            return;
+        }
+        let modified = std::mem::take(&mut self.memory.modified_code);
+        let volatile = &mut self.blocks.volatile;
+        let code = Arc::make_mut(&mut self.memory.code);
+        for &address in &modified {
+            if volatile.len() <= address {
+                volatile.resize(address + 1, false);
+            }
+            volatile[address] = true;
+            code[address] = false;
+        }
+        for start in self.blocks.covering(&modified) {
+            let block = self.compile_block(start);
+            insert(&mut self.blocks.local, start, block);
+        }
+    }
+
+    /// Runs compiled blocks until the machine stops for input, output or halt, or gets to an
+    /// instruction only the interpreter can run.
+    pub(super) fn run_compiled(&mut self) -> Result<(), EmulatorError<Word>> {
+        while matches!(self.state, State::Running) {
+            self.recompile_modified_code();
+            let start = self.instruction_pointer;
+            if start >= self.memory.image_end() {
+                return Ok(());
+            }
+            let block = match self.blocks.get(start) {
+                Some(block) => block.clone(),
+                None => {
+                    let block = self.compile_block(start);
+                    insert(&mut self.blocks.local, start, block.clone());
+                    block
+                }
+            };
+            if block.is_empty() {
+                return Ok(());
+            }
+            for instruction in block.iter() {
+                self.execute_compiled(instruction)?;
+                self.paused_at_breakpoint = false;
+                if !self.memory.modified_code.is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles blocks back to back over the whole program, the way `predecode` decodes it, so
+    /// forks of this machine don't each compile them again.
+    fn precompile(&mut self) {
+        self.drop_compiled_code();
+        let mut shared = Vec::new();
+        let mut start = 0;
+        while start < self.memory.image_end() {
+            let block = self.compile_block(start);
+            let next = block.last().map_or(start + 1, |last| last.next);
+            insert(&mut shared, start, block);
+            start = next;
+        }
+        let ip = self.instruction_pointer;
+        if ip < self.memory.image_end() && matches!(shared.get(ip), None | Some(None)) {
+            let block = self.compile_block(ip);
+            insert(&mut shared, ip, block);
+        }
+        self.blocks.shared = Arc::new(shared);
+    }
+
+    fn compile_block(&mut self, start: Address) -> Block<Word> {
+        let mut instructions = Vec::new();
+        let mut address = start;
+        while let Some(instruction) = self.compile_instruction(address) {
+            address = instruction.next;
+            let ends_block = instruction.op.ends_block();
+            instructions.push(instruction);
+            if ends_block {
+                break;
+            }
+        }
+        let code = Arc::make_mut(&mut self.memory.code);
+        if code.len() < address {
+            code.resize(address, false);
+        }
+        code[start..address]
+            .iter_mut()
+            .for_each(|word| *word = true);
+        Block::from(instructions)
+    }
+
+    fn compile_instruction(&self, address: Address) -> Option<Instruction<Word>> {
+        if address >= self.memory.image_end() || self.check_address(address).is_err() {
+            return None;
+        }
+        let decoded = Self::decode_instruction(&self.memory[address], address).ok()?;
+        let next = address + decoded.info.size();
+        if next > self.memory.image_end() {
+            return None;
+        }
+        let volatile = &self.blocks.volatile;
+        if (address..next).any(|word| volatile.get(word) == Some(&true)) {
+            return None;
+        }
+        let operands = decoded
+            .info
+            .operands
+            .iter()
+            .zip(decoded.operand_modes())
+            .enumerate()
+            .map(|(index, (&kind, &mode))| {
+                compile_operand(&self.memory[address + 1 + index], mode, kind)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let mut operands = operands.into_iter();
+        let mut operand = || operands.next().unwrap();
+        let op = match decoded.info.opcode {
+            Opcode::Add => Op::Add(operand(), operand(), operand()),
+            Opcode::Mul => Op::Mul(operand(), operand(), operand()),
+            Opcode::Input => Op::Input(operand()),
+            Opcode::Output => Op::Output(operand()),
+            Opcode::JumpIfTrue => Op::JumpIfTrue(operand(), operand()),
+            Opcode::JumpIfFalse => Op::JumpIfFalse(operand(), operand()),
+            Opcode::LessThan => Op::LessThan(operand(), operand(), operand()),
+            Opcode::Equals => Op::Equals(operand(), operand(), operand()),
+            Opcode::AddToRelativeBase => Op::AddToRelativeBase(operand()),
+            Opcode::Halt => Op::Halt,
+        };
+        Some(Instruction { address, next, op })
+    }
+
+    fn read(&self, operand: &Operand<Word>) -> Result<Word, EmulatorError<Word>> {
+        let address = match operand {
+            Operand::Immediate(value) => return Ok(value.clone()),
+            Operand::Position(address) => self.check_address(*address)?,
+            Operand::Relative(offset) => self.relative_address(offset)?,
+        };
+        Ok(self.memory[address].clone())
+    }
+
+    fn target(&self, operand: &Operand<Word>) -> Result<Address, EmulatorError<Word>> {
+        match operand {
+            Operand::Position(address) => self.check_address(*address),
+            Operand::Relative(offset) => self.relative_address(offset),
+            Operand::Immediate(_) => unreachable!("immediate write operands are never compiled"),
+        }
+    }
+
+    fn execute_compiled(
+        &mut self,
+        instruction: &Instruction<Word>,
+    ) -> Result<(), EmulatorError<Word>> {
+        self.instruction_pointer = instruction.address;
+        let mut next = instruction.next;
+        match &instruction.op {
+            Op::Add(a, b, write) => {
+                let (a, b, write) = (self.read(a)?, self.read(b)?, self.target(write)?);
+                self.memory[write] = self.arithmetic("add", a, b, Word::checked_add, Add::add)?;
+            }
+            Op::Mul(a, b, write) => {
+                let (a, b, write) = (self.read(a)?, self.read(b)?, self.target(write)?);
+                self.memory[write] = self.arithmetic("mul", a, b, Word::checked_mul, Mul::mul)?;
+            }
+            Op::Input(write) => self.state = State::RequestingInput(self.target(write)?),
+            Op::Output(read) => self.state = State::HoldingOutput(self.read(read)?),
+            Op::JumpIfTrue(test, jump) => {
+                let (test, jump) = (self.read(test)?, self.read(jump)?);
+                if !test.is_zero() {
+                    next = self.word_to_address(&jump)?;
+                }
+            }
+            Op::JumpIfFalse(test, jump) => {
+                let (test, jump) = (self.read(test)?, self.read(jump)?);
+                if test.is_zero() {
+                    next = self.word_to_address(&jump)?;
+                }
+            }
+            Op::LessThan(a, b, write) => {
+                let (a, b, write) = (self.read(a)?, self.read(b)?, self.target(write)?);
+                self.memory[write] = if a < b { Word::one() } else { Word::zero() };
+            }
+            Op::Equals(a, b, write) => {
+                let (a, b, write) = (self.read(a)?, self.read(b)?, self.target(write)?);
+                self.memory[write] = if a == b { Word::one() } else { Word::zero() };
+            }
+            Op::AddToRelativeBase(offset) => {
+                let offset = self.read(offset)?;
+                let base = self.relative_base_offset.clone();
+                self.relative_base_offset = self.arithmetic(
+                    "add_to_relative_base",
+                    base,
+                    offset,
+                    Word::checked_add,
+                    Add::add,
+                )?;
+            }
+            Op::Halt => self.state = State::Halt,
+        }
+        self.instruction_pointer = next;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::{parse_intcode_text, RunResult, PAGE_SIZE};
+
+    /// Runs a program on both tiers side by side, feeding both the same pseudo-random inputs.
+    fn run_both_tiers(program: &[i128], mut seed: u64) {
+        let mut interpreter = Emulator::new(program.to_owned());
+        let mut compiled = Emulator::new(program.to_owned());
+        compiled.set_tier(Tier::Compiled);
+        for _ in 0..2000 {
+            let result = interpreter.run();
+            assert_eq!(compiled.run(), result);
+            assert_eq!(
+                compiled.instruction_pointer(),
+                interpreter.instruction_pointer()
+            );
+            assert_eq!(
+                compiled.relative_base_offset(),
+                interpreter.relative_base_offset()
+            );
+            match result {
+                Ok(RunResult::InputRequest) => {
+                    seed = seed
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    let input = (seed >> 33) as i128 % 5 - 1;
+                    interpreter.push_input(input);
+                    compiled.push_input(input);
+                }
+                Ok(RunResult::Output(_)) => {}
+                _ => break,
+            }
+        }
+        assert_eq!(compiled.into_memory(), interpreter.into_memory());
+    }
+
+    #[test]
+    fn test_tiers_agree_on_puzzles() {
+        let inputs: [&str; 5] = [
+            include_str!("../../../input/2019/day5.txt"),
+            include_str!("../../../input/2019/day9.txt"),
+            include_str!("../../../input/2019/day11.txt"),
+            include_str!("../../../input/2019/day13.txt"),
+            include_str!("../../../input/2019/day15.txt"),
+        ];
+        for input in inputs.iter() {
+            let program = parse_intcode_text(input.trim().as_bytes()).unwrap();
+            for seed in 0..3 {
+                run_both_tiers(&program, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_modification() {
+        let program = vec![
+            104, 5, // output 5, then output [5] once rewritten to 4
+            1005, 16, 17, // halt on the second pass
+            1101, 0, 4, 0, // [0] = 4
+            1101, 0, 1, 16, // [16] = 1
+            1105, 1, 0, // jump to 0
+            0, 99,
+        ];
+        let mut emulator = Emulator::new(program);
+        emulator.set_tier(Tier::Compiled);
+        assert_eq!(emulator.run_to_completion(None), Ok(vec![5, 1101]));
+        assert_eq!(emulator.blocks.volatile, [true]);
+
+        // writes from outside count too
+        let mut emulator = Emulator::new(vec![104, 9, 1105, 1, 0]);
+        emulator.set_tier(Tier::Compiled);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(9)));
+        emulator.write_memory(1, 7);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(7)));
+        assert_eq!(emulator.blocks.volatile, [false, true]);
+    }
+
+    #[test]
+    fn test_far_code() {
+        let far = 1i128 << 36;
+        let programs = [
+            vec![1105, 1, far],
+            // writes `output #7, halt` far away and jumps there
+            vec![
+                1101,
+                0,
+                104,
+                far,
+                1101,
+                0,
+                7,
+                far + 1,
+                1101,
+                0,
+                99,
+                far + 2,
+                1105,
+                1,
+                far,
+            ],
+        ];
+        for program in programs.iter() {
+            run_both_tiers(program, 0);
+        }
+
+        let mut emulator = Emulator::new(programs[1].clone());
+        emulator.add_breakpoint(12);
+        assert_eq!(emulator.run(), Ok(RunResult::Breakpoint(12)));
+        emulator.set_tier(Tier::Compiled);
+        emulator.remove_breakpoint(12);
+        assert_eq!(emulator.run_to_completion(None), Ok(vec![7]));
+        assert!(emulator.memory.code.len() <= PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_errors_inside_blocks() {
+        let programs = [
+            vec![1101, 1, 2, 20, 109, -5, 204, 0, 99],
+            vec![1101, 1, 2, 20, 1102, 1, 2, -1, 99],
+            vec![1101, 1, 2, 20, 11101, 1, 2, 3, 99],
+            vec![1101, 1, 2, 20, 1105, 1, -9, 99],
+            vec![1101, 1, 2, 20, 1101, 1, 2, 100, 99],
+            vec![1101, 1, 2, 20, 42, 99],
+        ];
+        for program in programs.iter() {
+            let mut interpreter = Emulator::with_memory_limit(program.clone(), 50);
+            let mut compiled = Emulator::with_memory_limit(program.clone(), 50);
+            compiled.set_tier(Tier::Compiled);
+            let error = interpreter.run().unwrap_err();
+            assert_eq!(compiled.run(), Err(error));
+            assert_eq!(
+                compiled.instruction_pointer(),
+                interpreter.instruction_pointer()
+            );
+            assert_eq!(compiled.into_memory(), interpreter.into_memory());
+        }
+    }
+}
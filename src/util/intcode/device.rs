@@ -0,0 +1,253 @@
+//! Devices an intcode program talks to through its input and output instructions.
+//!
+//! An `InputSource` hands out values when the program asks for input, an `OutputSink` takes
+//! every value it outputs. `Emulator::run_with` and `Emulator::run_device` drive a machine
+//! against them, so a solution only has to describe the protocol spoken over the wire.
+
+use super::{Emulator, EmulatorError, IntcodeWord, RunResult};
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+
+pub trait InputSource<Word> {
+    /// Returns the next input value, or `None` if there is none to give right now.
+    fn next_input(&mut self) -> Option<Word>;
+}
+
+pub trait OutputSink<Word> {
+    fn write_output(&mut self, value: Word);
+}
+
+impl<Word, S> InputSource<Word> for &mut S
+where
+    S: InputSource<Word> + ?Sized,
+{
+    fn next_input(&mut self) -> Option<Word> {
+        (**self).next_input()
+    }
+}
+
+impl<Word, S> OutputSink<Word> for &mut S
+where
+    S: OutputSink<Word> + ?Sized,
+{
+    fn write_output(&mut self, value: Word) {
+        (**self).write_output(value)
+    }
+}
+
+impl<Word> InputSource<Word> for VecDeque<Word> {
+    fn next_input(&mut self) -> Option<Word> {
+        self.pop_front()
+    }
+}
+
+impl<Word> OutputSink<Word> for VecDeque<Word> {
+    fn write_output(&mut self, value: Word) {
+        self.push_back(value)
+    }
+}
+
+impl<Word> OutputSink<Word> for Vec<Word> {
+    fn write_output(&mut self, value: Word) {
+        self.push(value)
+    }
+}
+
+/// Blocks until a value arrives, and runs dry once every sender is gone.
+impl<Word> InputSource<Word> for Receiver<Word> {
+    fn next_input(&mut self) -> Option<Word> {
+        self.recv().ok()
+    }
+}
+
+/// Values sent after the receiver hung up are dropped.
+impl<Word> OutputSink<Word> for Sender<Word> {
+    fn write_output(&mut self, value: Word) {
+        let _ = self.send(value);
+    }
+}
+
+/// Adapts a closure into a device; see `from_fn`.
+#[derive(Debug, Clone)]
+pub struct FromFn<F>(F);
+
+/// Wraps `FnMut() -> Option<Word>` as an input source, or `FnMut(Word)` as an output sink.
+pub fn from_fn<F>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+impl<Word, F> InputSource<Word> for FromFn<F>
+where
+    F: FnMut() -> Option<Word>,
+{
+    fn next_input(&mut self) -> Option<Word> {
+        (self.0)()
+    }
+}
+
+impl<Word, F> OutputSink<Word> for FromFn<F>
+where
+    F: FnMut(Word),
+{
+    fn write_output(&mut self, value: Word) {
+        (self.0)(value)
+    }
+}
+
+/// Adapts an iterator into an input source; see `from_iter`.
+#[derive(Debug, Clone)]
+pub struct FromIter<I>(I);
+
+pub fn from_iter<I: IntoIterator>(iter: I) -> FromIter<I::IntoIter> {
+    FromIter(iter.into_iter())
+}
+
+impl<Word, I> InputSource<Word> for FromIter<I>
+where
+    I: Iterator<Item = Word>,
+{
+    fn next_input(&mut self) -> Option<Word> {
+        self.0.next()
+    }
+}
+
+/// An input source and an output sink wired up as a single device.
+struct Wired<I, O> {
+    input: I,
+    output: O,
+}
+
+impl<Word, I, O> InputSource<Word> for Wired<I, O>
+where
+    I: InputSource<Word>,
+{
+    fn next_input(&mut self) -> Option<Word> {
+        self.input.next_input()
+    }
+}
+
+impl<Word, I, O> OutputSink<Word> for Wired<I, O>
+where
+    O: OutputSink<Word>,
+{
+    fn write_output(&mut self, value: Word) {
+        self.output.write_output(value)
+    }
+}
+
+impl<Word> Emulator<Word>
+where
+    Word: IntcodeWord,
+{
+    /// Runs the program against a device that both provides input and consumes output.
+    ///
+    /// Returns `RunResult::Halt` once the program halts, or `RunResult::InputRequest` if it
+    /// asked for input the device could not give; the machine can be resumed after that.
+    /// Breakpoints and watchpoints stop the driver like they stop `run`.
+    pub fn run_device<D>(&mut self, device: &mut D) -> Result<RunResult<Word>, EmulatorError<Word>>
+    where
+        D: InputSource<Word> + OutputSink<Word> + ?Sized,
+    {
+        loop {
+            match self.run()? {
+                RunResult::Output(value) => device.write_output(value),
+                RunResult::InputRequest => match device.next_input() {
+                    Some(value) => self.push_input(value),
+                    None => return Ok(RunResult::InputRequest),
+                },
+                result => return Ok(result),
+            }
+        }
+    }
+
+    /// Like `run_device`, with input and output handled by two separate devices.
+    pub fn run_with(
+        &mut self,
+        input: impl InputSource<Word>,
+        output: impl OutputSink<Word>,
+    ) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        self.run_device(&mut Wired { input, output })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    fn add_pairs() -> Emulator<i64> {
+        // outputs the sum of each pair of inputs, forever
+        Emulator::new(vec![
+            3, 13, 3, 14, 1, 13, 14, 15, 4, 15, 1105, 1, 0, 0, 0, 0,
+        ])
+    }
+
+    #[test]
+    fn test_queue_devices() {
+        let mut emulator = add_pairs();
+        let mut output = Vec::new();
+        let input = (1..=5).collect::<VecDeque<_>>();
+        let result = emulator.run_with(input, &mut output);
+        assert_eq!(result, Ok(RunResult::InputRequest));
+        assert_eq!(output, vec![3, 7]);
+
+        // the sum is fed straight back in as the first half of the next pair
+        let mut queue = VecDeque::from(vec![10]);
+        assert_eq!(emulator.run_device(&mut queue), Ok(RunResult::InputRequest));
+        assert!(queue.is_empty());
+        let mut output = Vec::new();
+        let result = emulator.run_with(from_iter(vec![1]), &mut output);
+        assert_eq!(result, Ok(RunResult::InputRequest));
+        assert_eq!(output, vec![16]);
+    }
+
+    #[test]
+    fn test_closure_devices() {
+        let mut emulator = add_pairs();
+        let mut total = 0;
+        let result =
+            emulator.run_with(from_iter(vec![1, 2, 3, 4]), from_fn(|value| total += value));
+        assert_eq!(result, Ok(RunResult::InputRequest));
+        assert_eq!(total, 10);
+
+        let mut next = 0;
+        let input = from_fn(|| {
+            next += 1;
+            Some(next).filter(|&n| n <= 6)
+        });
+        let mut output = Vec::new();
+        assert_eq!(
+            emulator.run_with(input, &mut output),
+            Ok(RunResult::InputRequest)
+        );
+        assert_eq!(output, vec![3, 7, 11]);
+    }
+
+    #[test]
+    fn test_halts() {
+        let mut emulator = Emulator::new(vec![3, 0, 4, 0, 99]);
+        let mut output = Vec::new();
+        assert_eq!(
+            emulator.run_with(from_iter(vec![42]), &mut output),
+            Ok(RunResult::Halt)
+        );
+        assert_eq!(output, vec![42]);
+    }
+
+    #[test]
+    fn test_channel_devices() {
+        let (input_sender, input_receiver) = channel();
+        let (output_sender, output_receiver) = channel();
+        let machine =
+            thread::spawn(move || add_pairs().run_with(input_receiver, output_sender).unwrap());
+        for value in 1..=4 {
+            input_sender.send(value).unwrap();
+        }
+        assert_eq!(output_receiver.recv(), Ok(3));
+        assert_eq!(output_receiver.recv(), Ok(7));
+        drop(input_sender);
+        assert_eq!(machine.join().unwrap(), RunResult::InputRequest);
+        assert!(output_receiver.recv().is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Turns intcode images into readable listings of instructions and data, like
+//! `0: add #1, #2, [5]`.
+
+use super::{Address, DecodedInstruction, Emulator, IntcodeWord, Opcode, OperandMode};
+use itertools::Itertools;
+use std::fmt;
+
+const DATA_WORDS_PER_LINE: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line<Word> {
+    Instruction {
+        address: Address,
+        decoded: DecodedInstruction,
+        operands: Vec<Word>,
+    },
+    Data {
+        address: Address,
+        words: Vec<Word>,
+    },
+}
+
+impl<Word> Line<Word> {
+    pub fn address(&self) -> Address {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing<Word> {
+    pub lines: Vec<Line<Word>>,
+}
+
+pub fn format_operand<Word>(value: &Word, mode: OperandMode) -> String
+where
+    Word: fmt::Display,
+{
+    match mode {
+        OperandMode::Position => format!("[{}]", value),
+        OperandMode::Immediate => format!("#{}", value),
+        OperandMode::Relative => {
+            let value = value.to_string();
+            if value.starts_with('-') {
+                format!("rb{}", value)
+            } else {
+                format!("rb+{}", value)
+            }
+        }
+    }
+}
+
+impl<Word> fmt::Display for Line<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Instruction {
+                address,
+                decoded,
+                operands,
+            } => {
+                write!(f, "{:>6}: {}", address, decoded.info.mnemonic)?;
+                if !operands.is_empty() {
+                    write!(
+                        f,
+                        " {}",
+                        operands
+                            .iter()
+                            .zip(decoded.operand_modes())
+                            .map(|(value, &mode)| format_operand(value, mode))
+                            .format(", ")
+                    )?;
+                }
+                Ok(())
+            }
+            Line::Data { address, words } => {
+                write!(f, "{:>6}: data {}", address, words.iter().format(", "))
+            }
+        }
+    }
+}
+
+impl<Word> fmt::Display for Listing<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Control flow facts about a single decoded instruction, used to find reachable code.
+pub(crate) struct Flow {
+    pub falls_through: bool,
+    pub jump_target: Option<Address>,
+    pub indirect_jump: bool,
+}
+
+pub(crate) fn flow_of<Word>(decoded: &DecodedInstruction, operands: &[Word]) -> Flow
+where
+    Word: IntcodeWord,
+{
+    match decoded.info.opcode {
+        Opcode::Halt => Flow {
+            falls_through: false,
+            jump_target: None,
+            indirect_jump: false,
+        },
+        Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+            let jumps_on_nonzero = decoded.info.opcode == Opcode::JumpIfTrue;
+            let always_jumps = match decoded.modes[0] {
+                OperandMode::Immediate => Some(operands[0].is_zero() != jumps_on_nonzero),
+                _ => None,
+            };
+            let (jump_target, indirect_jump) = match decoded.modes[1] {
+                OperandMode::Immediate => (operands[1].to_address(), false),
+                _ => (None, true),
+            };
+            Flow {
+                falls_through: always_jumps != Some(true),
+                jump_target: jump_target.filter(|_| always_jumps != Some(false)),
+                indirect_jump: indirect_jump && always_jumps != Some(false),
+            }
+        }
+        Opcode::Add
+        | Opcode::Mul
+        | Opcode::Input
+        | Opcode::Output
+        | Opcode::LessThan
+        | Opcode::Equals
+        | Opcode::AddToRelativeBase => Flow {
+            falls_through: true,
+            jump_target: None,
+            indirect_jump: false,
+        },
+    }
+}
+
+pub(crate) fn decode_at<Word>(
+    program: &[Word],
+    address: Address,
+) -> Option<(DecodedInstruction, Vec<Word>)>
+where
+    Word: IntcodeWord,
+{
+    let decoded = Emulator::decode_instruction(&program[address], address).ok()?;
+    let operands = program.get(address + 1..address + decoded.info.size())?;
+    Some((decoded, operands.to_vec()))
+}
+
+/// Disassembles an intcode image.
+///
+/// Code is found by following control flow from address 0 through every jump with an
+/// immediate target. The gaps left over are swept linearly: words that decode to a valid
+/// instruction are listed as code, everything else as data.
+pub fn disassemble<Word>(program: &[Word]) -> Listing<Word>
+where
+    Word: IntcodeWord,
+{
+    let mut code_start = vec![false; program.len()];
+    let mut covered = vec![false; program.len()];
+    let mut worklist = vec![0];
+    while let Some(address) = worklist.pop() {
+        if address >= program.len() || covered[address] {
+            continue;
+        }
+        let (decoded, operands) = match decode_at(program, address) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        let end = address + decoded.info.size();
+        if covered[address..end].iter().any(|&c| c) {
+            continue;
+        }
+        code_start[address] = true;
+        covered[address..end].iter_mut().for_each(|c| *c = true);
+
+        let flow = flow_of(&decoded, &operands);
+        if flow.falls_through {
+            worklist.push(end);
+        }
+        worklist.extend(flow.jump_target);
+    }
+
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let instruction = decode_at(program, address).filter(|(decoded, _)| {
+            let end = address + decoded.info.size();
+            code_start[address] || !covered[address..end].iter().any(|&c| c)
+        });
+        match instruction {
+            Some((decoded, operands)) => {
+                lines.push(Line::Instruction {
+                    address,
+                    decoded,
+                    operands,
+                });
+                address += decoded.info.size();
+            }
+            None => {
+                match lines.last_mut() {
+                    Some(Line::Data {
+                        address: start,
+                        words,
+                    }) if words.len() < DATA_WORDS_PER_LINE && *start + words.len() == address => {
+                        words.push(program[address].clone())
+                    }
+                    _ => lines.push(Line::Data {
+                        address,
+                        words: vec![program[address].clone()],
+                    }),
+                }
+                address += 1;
+            }
+        }
+    }
+    Listing { lines }
+}
+
+/// Decodes `count` consecutive lines starting at `address`, without any control flow analysis.
+///
+/// Useful for looking at live memory around the instruction pointer, where the caller knows
+/// the address is the start of an instruction. Stops early at the end of the address space.
+pub fn disassemble_at<Word>(
+    read: impl Fn(Address) -> Word,
+    address: Address,
+    count: usize,
+) -> Vec<Line<Word>>
+where
+    Word: IntcodeWord,
+{
+    let mut lines = Vec::with_capacity(count);
+    let mut next = Some(address);
+    while let Some(address) = next.filter(|_| lines.len() < count) {
+        let word = read(address);
+        let decoded = Emulator::decode_instruction(&word, address)
+            .ok()
+            .filter(|decoded| address.checked_add(decoded.info.size()).is_some());
+        let line = match decoded {
+            Some(decoded) => Line::Instruction {
+                address,
+                decoded,
+                operands: (1..decoded.info.size())
+                    .map(|offset| read(address + offset))
+                    .collect(),
+            },
+            None => Line::Data {
+                address,
+                words: vec![word],
+            },
+        };
+        next = address.checked_add(match &line {
+            Line::Instruction { decoded, .. } => decoded.info.size(),
+            Line::Data { .. } => 1,
+        });
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+        assert_eq!(
+            listing.to_string(),
+            "     0: add [9], [10], [3]\n     \
+             4: mul [3], [11], [0]\n     \
+             8: halt\n     \
+             9: data 30, 40, 50\n"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_modes() {
+        let listing = disassemble(&[109, -3, 21101, 4, 5, 7, 204, -1, 99]);
+        assert_eq!(
+            listing.to_string(),
+            "     0: add_to_relative_base #-3\n     \
+             2: add #4, #5, rb+7\n     \
+             6: output rb-1\n     \
+             8: halt\n"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_follows_jumps() {
+        // jumps over a word that would otherwise swallow the next instruction
+        let listing = disassemble(&[1105, 1, 4, 1, 104, 0, 99, 42, 1301]);
+        let addresses = listing
+            .lines
+            .iter()
+            .map(|line| line.address())
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, vec![0, 3, 4, 6, 7]);
+        assert_eq!(
+            listing.lines[1],
+            Line::Data {
+                address: 3,
+                words: vec![1]
+            }
+        );
+        assert_eq!(
+            listing.lines[4],
+            Line::Data {
+                address: 7,
+                words: vec![42, 1301]
+            }
+        );
+    }
+
+    #[test]
+    fn test_disassemble_at() {
+        let program = [1105, 1, 4, 1, 104, 0, 99, 42];
+        let lines = disassemble_at(|address| program.get(address).cloned().unwrap_or(0), 4, 4)
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "     4: output #0",
+                "     6: halt",
+                "     7: data 42",
+                "     8: data 0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_disassemble_at_end_of_memory() {
+        let lines = disassemble_at(|_| 99i64, Address::MAX - 1, 5);
+        let addresses: Vec<_> = lines.iter().map(Line::address).collect();
+        assert_eq!(addresses, vec![Address::MAX - 1, Address::MAX]);
+        let lines = disassemble_at(|_| 1101i64, Address::MAX - 1, 5);
+        assert!(matches!(lines[..], [Line::Data { .. }, Line::Data { .. }]));
+    }
+
+    #[test]
+    fn test_mnemonics_match_emulator() {
+        for info in Emulator::<i64>::INSTRUCTIONS {
+            let decoded =
+                Emulator::<i64>::decode_instruction(&(info.opcode as usize as i64), 0).unwrap();
+            assert_eq!(decoded.info, info);
+        }
+    }
+}
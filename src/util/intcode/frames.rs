@@ -0,0 +1,213 @@
+//! Splitting a program's output into fixed-size messages, like the `x, y, tile` triples of an
+//! arcade cabinet.
+//!
+//! `Emulator::frames` yields whole frames straight from a machine, `FrameBuffer` does the same
+//! for devices that receive one word at a time. Either way, a program that stops in the middle
+//! of a frame is an error instead of a silently dropped or misaligned message.
+
+use super::{Emulator, EmulatorError, IntcodeWord, RunResult};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError<Word> {
+    Emulator(EmulatorError<Word>),
+    /// The program stopped for `stop` with only `words` of an `expected`-word frame written.
+    Incomplete {
+        words: Vec<Word>,
+        expected: usize,
+        stop: RunResult<Word>,
+    },
+}
+
+impl<Word> fmt::Display for FrameError<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Emulator(err) => err.fmt(f),
+            FrameError::Incomplete {
+                words,
+                expected,
+                stop,
+            } => {
+                match stop {
+                    RunResult::Halt => write!(f, "program halted")?,
+                    RunResult::InputRequest => write!(f, "program requested input")?,
+                    RunResult::Breakpoint(address) => write!(f, "breakpoint at {}", address)?,
+                    RunResult::Watchpoint(address) => write!(f, "watchpoint at {}", address)?,
+                    RunResult::Output(_) | RunResult::OutOfFuel => write!(f, "program stopped")?,
+                }
+                write!(f, " after {} of {} words of a frame", words.len(), expected)
+            }
+        }
+    }
+}
+
+impl<Word> Error for FrameError<Word> where Word: fmt::Debug + fmt::Display {}
+
+impl<Word> From<EmulatorError<Word>> for FrameError<Word> {
+    fn from(err: EmulatorError<Word>) -> Self {
+        FrameError::Emulator(err)
+    }
+}
+
+/// Collects single output words into frames of `N`.
+#[derive(Debug, Clone)]
+pub struct FrameBuffer<Word, const N: usize> {
+    words: Vec<Word>,
+}
+
+impl<Word, const N: usize> Default for FrameBuffer<Word, N> {
+    fn default() -> Self {
+        Self {
+            words: Vec::with_capacity(N),
+        }
+    }
+}
+
+impl<Word, const N: usize> FrameBuffer<Word, N> {
+    /// Adds a word, returning the frame it completes, if any.
+    pub fn push(&mut self, word: Word) -> Option<[Word; N]> {
+        self.words.push(word);
+        if self.words.len() < N {
+            return None;
+        }
+        let words = std::mem::replace(&mut self.words, Vec::with_capacity(N));
+        <[Word; N]>::try_from(words).ok()
+    }
+
+    /// Checks that no partial frame is left over after the program stopped for `stop`.
+    pub fn finish(&mut self, stop: RunResult<Word>) -> Result<(), FrameError<Word>> {
+        if self.words.is_empty() {
+            Ok(())
+        } else {
+            Err(FrameError::Incomplete {
+                words: std::mem::take(&mut self.words),
+                expected: N,
+                stop,
+            })
+        }
+    }
+}
+
+/// Iterator returned by `Emulator::frames`.
+pub struct Frames<'a, Word, const N: usize> {
+    emulator: &'a mut Emulator<Word>,
+    buffer: FrameBuffer<Word, N>,
+    stop: Option<RunResult<Word>>,
+    failed: bool,
+}
+
+impl<Word, const N: usize> Frames<'_, Word, N> {
+    /// Why the iterator ended, like `OutputIter::stop`.
+    pub fn stop(&self) -> Option<&RunResult<Word>> {
+        self.stop.as_ref()
+    }
+}
+
+impl<Word, const N: usize> Iterator for Frames<'_, Word, N>
+where
+    Word: IntcodeWord,
+{
+    type Item = Result<[Word; N], FrameError<Word>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stop.is_some() || self.failed {
+            return None;
+        }
+        loop {
+            match self.emulator.run() {
+                Ok(RunResult::Output(value)) => {
+                    if let Some(frame) = self.buffer.push(value) {
+                        return Some(Ok(frame));
+                    }
+                }
+                Ok(stop) => {
+                    if let Err(err) = self.buffer.finish(stop.clone()) {
+                        self.failed = true;
+                        return Some(Err(err));
+                    }
+                    self.stop = Some(stop);
+                    return None;
+                }
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
+}
+
+impl<Word> Emulator<Word>
+where
+    Word: IntcodeWord,
+{
+    /// Iterates over the program's output in frames of `N` words, until it halts or needs
+    /// more input.
+    ///
+    /// Stopping with a partial frame written yields `FrameError::Incomplete` and ends the
+    /// iterator.
+    pub fn frames<const N: usize>(&mut self) -> Frames<'_, Word, N> {
+        Frames {
+            emulator: self,
+            buffer: FrameBuffer::default(),
+            stop: None,
+            failed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames() {
+        let mut emulator = Emulator::new(vec![104, 1, 104, 2, 3, 13, 104, 3, 104, 4, 99]);
+        let mut frames = emulator.frames::<2>();
+        assert_eq!(frames.next(), Some(Ok([1, 2])));
+        assert_eq!(frames.next(), None);
+        assert_eq!(frames.stop(), Some(&RunResult::InputRequest));
+
+        emulator.push_input(0);
+        let frames = emulator.frames().collect::<Result<Vec<_>, _>>();
+        assert_eq!(frames, Ok(vec![[3, 4]]));
+        assert!(emulator.is_halted());
+    }
+
+    #[test]
+    fn test_incomplete_frame() {
+        let mut emulator = Emulator::new(vec![104, 1, 104, 2, 104, 3, 104, 4, 99]);
+        let mut frames = emulator.frames::<3>();
+        assert_eq!(frames.next(), Some(Ok([1, 2, 3])));
+        let err = frames.next().unwrap().unwrap_err();
+        assert_eq!(
+            err,
+            FrameError::Incomplete {
+                words: vec![4],
+                expected: 3,
+                stop: RunResult::Halt
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "program halted after 1 of 3 words of a frame"
+        );
+        assert_eq!(frames.next(), None);
+    }
+
+    #[test]
+    fn test_frame_buffer() {
+        let mut buffer = FrameBuffer::<i64, 2>::default();
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), Some([1, 2]));
+        assert_eq!(buffer.finish(RunResult::Halt), Ok(()));
+        assert_eq!(buffer.push(3), None);
+        assert!(buffer.finish(RunResult::InputRequest).is_err());
+        assert_eq!(buffer.push(4), None);
+    }
+}
@@ -0,0 +1,306 @@
+//! Several emulators wired together, each output of one machine becoming input of others.
+//!
+//! Machines run cooperatively on the calling thread: the scheduler takes turns running every
+//! machine that can make progress, until all of them have halted or are stuck waiting for input
+//! nobody is going to send.
+
+pub mod threaded;
+
+use super::{Emulator, EmulatorError, IntcodeWord, RunResult};
+use std::error::Error;
+use std::fmt;
+
+/// Index of a machine in a `Network`, in the order machines were added.
+pub type MachineId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MachineState<Word> {
+    /// Not run yet, or has input it hasn't consumed.
+    Ready,
+    /// Waiting for input.
+    Blocked,
+    Halted,
+    /// Stopped at a breakpoint or watchpoint; the scheduler won't run it again.
+    Stopped(RunResult<Word>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineReport<Word> {
+    pub state: MachineState<Word>,
+    pub last_output: Option<Word>,
+    pub output_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkReport<Word> {
+    pub machines: Vec<MachineReport<Word>>,
+}
+
+impl<Word> NetworkReport<Word> {
+    pub fn all_halted(&self) -> bool {
+        self.machines
+            .iter()
+            .all(|machine| matches!(machine.state, MachineState::Halted))
+    }
+
+    /// Whether the run ended with machines still waiting for input.
+    pub fn is_deadlocked(&self) -> bool {
+        self.machines
+            .iter()
+            .any(|machine| matches!(machine.state, MachineState::Blocked))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkError<Word> {
+    pub machine: MachineId,
+    pub error: EmulatorError<Word>,
+}
+
+impl<Word> fmt::Display for NetworkError<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "machine {}: {}", self.machine, self.error)
+    }
+}
+
+impl<Word> Error for NetworkError<Word> where Word: fmt::Debug + fmt::Display {}
+
+#[derive(Debug)]
+struct Node<Word> {
+    emulator: Emulator<Word>,
+    links: Vec<MachineId>,
+    state: MachineState<Word>,
+    last_output: Option<Word>,
+    output_count: usize,
+    unrouted: Vec<Word>,
+}
+
+#[derive(Debug)]
+pub struct Network<Word> {
+    nodes: Vec<Node<Word>>,
+}
+
+impl<Word> Default for Network<Word> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<Word> Network<Word>
+where
+    Word: IntcodeWord,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Machines linked one after the other, the last one's output left unrouted.
+    pub fn chain(emulators: impl IntoIterator<Item = Emulator<Word>>) -> Self {
+        let mut network = Self::new();
+        for emulator in emulators {
+            let id = network.add_machine(emulator);
+            if id > 0 {
+                network.connect(id - 1, id);
+            }
+        }
+        network
+    }
+
+    /// A chain whose last machine feeds back into the first.
+    pub fn ring(emulators: impl IntoIterator<Item = Emulator<Word>>) -> Self {
+        let mut network = Self::chain(emulators);
+        if let Some(last) = network.nodes.len().checked_sub(1) {
+            network.connect(last, 0);
+        }
+        network
+    }
+
+    pub fn add_machine(&mut self, emulator: Emulator<Word>) -> MachineId {
+        self.nodes.push(Node {
+            emulator,
+            links: Vec::new(),
+            state: MachineState::Ready,
+            last_output: None,
+            output_count: 0,
+            unrouted: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Sends every output of `from` to `to` as well.
+    ///
+    /// A machine with several links broadcasts each output to all of them, in the order the
+    /// links were made. Outputs of a machine without links are kept, see `unrouted_outputs`.
+    /// Outputs sent to a machine that has halted or stopped are still queued as its input.
+    pub fn connect(&mut self, from: MachineId, to: MachineId) {
+        assert!(to < self.nodes.len(), "no machine {}", to);
+        self.nodes[from].links.push(to);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn machine(&self, id: MachineId) -> &Emulator<Word> {
+        &self.nodes[id].emulator
+    }
+
+    pub fn machine_mut(&mut self, id: MachineId) -> &mut Emulator<Word> {
+        &mut self.nodes[id].emulator
+    }
+
+    /// Queues input for a machine, from outside the network.
+    pub fn push_input(&mut self, id: MachineId, value: Word) -> Result<(), NetworkError<Word>> {
+        let node = &mut self.nodes[id];
+        node.emulator
+            .try_push_input(value)
+            .map_err(|error| NetworkError { machine: id, error })?;
+        if node.state == MachineState::Blocked {
+            node.state = MachineState::Ready;
+        }
+        Ok(())
+    }
+
+    /// Everything machine `id` output while it had no links.
+    pub fn unrouted_outputs(&self, id: MachineId) -> &[Word] {
+        &self.nodes[id].unrouted
+    }
+
+    /// Runs every machine that can make progress, round-robin, until none can.
+    ///
+    /// Each turn runs a machine until it halts or waits for input, delivering its outputs as
+    /// they happen. The run can be continued after queueing more input with `push_input`.
+    pub fn run(&mut self) -> Result<NetworkReport<Word>, NetworkError<Word>> {
+        loop {
+            let mut progressed = false;
+            for id in 0..self.nodes.len() {
+                if self.nodes[id].state == MachineState::Ready {
+                    self.run_machine(id)?;
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+        Ok(self.report())
+    }
+
+    fn run_machine(&mut self, id: MachineId) -> Result<(), NetworkError<Word>> {
+        loop {
+            let node = &mut self.nodes[id];
+            let result = node
+                .emulator
+                .run()
+                .map_err(|error| NetworkError { machine: id, error })?;
+            let state = match result {
+                RunResult::Output(value) => {
+                    node.last_output = Some(value.clone());
+                    node.output_count += 1;
+                    if node.links.is_empty() {
+                        node.unrouted.push(value.clone());
+                    }
+                    for target in node.links.clone() {
+                        self.push_input(target, value.clone())?;
+                    }
+                    continue;
+                }
+                RunResult::InputRequest => MachineState::Blocked,
+                RunResult::Halt => MachineState::Halted,
+                result => MachineState::Stopped(result),
+            };
+            self.nodes[id].state = state;
+            return Ok(());
+        }
+    }
+
+    pub fn report(&self) -> NetworkReport<Word> {
+        NetworkReport {
+            machines: self
+                .nodes
+                .iter()
+                .map(|node| MachineReport {
+                    state: node.state.clone(),
+                    last_output: node.last_output.clone(),
+                    output_count: node.output_count,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds `amount` to every input and outputs it, halting after `count` values.
+    fn adder(amount: i64, count: i64) -> Emulator<i64> {
+        Emulator::new(vec![
+            3, 100, 1001, 100, amount, 100, 4, 100, 1001, 101, 1, 101, 1008, 101, count, 102, 1006,
+            102, 0, 99,
+        ])
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut network = Network::chain(vec![adder(1, 2), adder(10, 2), adder(100, 2)]);
+        network.push_input(0, 0).unwrap();
+        network.push_input(0, 1000).unwrap();
+        let report = network.run().unwrap();
+        assert!(report.all_halted());
+        assert!(!report.is_deadlocked());
+        assert_eq!(network.unrouted_outputs(2), &[111, 1111]);
+        assert_eq!(report.machines[1].last_output, Some(1011));
+        assert!(network.unrouted_outputs(0).is_empty());
+    }
+
+    #[test]
+    fn test_ring() {
+        // passes a token around three machines, each halting after handling it twice
+        let mut network = Network::ring(vec![adder(1, 2), adder(1, 2), adder(1, 2)]);
+        network.push_input(0, 0).unwrap();
+        let report = network.run().unwrap();
+        assert_eq!(report.machines[2].last_output, Some(6));
+        assert_eq!(report.machines[2].state, MachineState::Halted);
+        assert_eq!(network.machine(0).pending_input(), 1);
+    }
+
+    #[test]
+    fn test_fan_in_and_deadlock() {
+        let mut network = Network::new();
+        let a = network.add_machine(adder(1, 1));
+        let b = network.add_machine(adder(2, 1));
+        let sum = network.add_machine(Emulator::new(vec![3, 20, 3, 21, 1, 20, 21, 22, 4, 22, 99]));
+        network.connect(a, sum);
+        network.connect(b, sum);
+        network.push_input(a, 10).unwrap();
+        let report = network.run().unwrap();
+        assert!(report.is_deadlocked());
+        assert_eq!(report.machines[b].state, MachineState::Blocked);
+        assert_eq!(report.machines[sum].state, MachineState::Blocked);
+
+        network.push_input(b, 20).unwrap();
+        let report = network.run().unwrap();
+        assert!(report.all_halted());
+        assert_eq!(network.unrouted_outputs(sum), &[33]);
+    }
+
+    #[test]
+    fn test_errors_name_the_machine() {
+        let mut network = Network::chain(vec![adder(1, 1), Emulator::new(vec![3, 0, 42])]);
+        network.push_input(0, 5).unwrap();
+        let error = network.run().unwrap_err();
+        assert_eq!(error.machine, 1);
+        assert_eq!(
+            error.to_string(),
+            "machine 1: invalid opcode 42 in instruction 42 at address 2"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Running a `Network` with every machine on its own thread, linked by channels.
+//!
+//! A machine waiting for input blocks on its channel. Deadlock is detected by counting: once
+//! every machine still alive is blocked and no message is in flight, nobody can ever send
+//! again, so every blocked machine is told to shut down.
+//!
+//! Values sent to a machine that halted or stopped are set aside and queued as its input once
+//! the run is over, so it ends up with the same pending input as after `Network::run`.
+
+use super::{MachineId, MachineState, Network, NetworkError, NetworkReport, Node};
+use crate::util::intcode::{IntcodeWord, RunResult};
+use std::panic;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+enum Message<Word> {
+    Value(Word),
+    Shutdown,
+}
+
+struct Counts<Word> {
+    alive: usize,
+    blocked: usize,
+    in_flight: usize,
+    retired: Vec<bool>,
+    /// Values sent to each retired machine, in the order they were sent.
+    undelivered: Vec<Vec<Word>>,
+    deadlocked: bool,
+}
+
+/// Bookkeeping shared by all machine threads. Every send happens under the lock, so the counts
+/// always agree with what is sitting in the channels.
+struct Monitor<Word> {
+    counts: Mutex<Counts<Word>>,
+    senders: Vec<Sender<Message<Word>>>,
+}
+
+impl<Word> Monitor<Word> {
+    /// Locks the counts, even after a machine thread panicked while holding the lock.
+    fn counts(&self) -> MutexGuard<'_, Counts<Word>> {
+        self.counts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn send(&self, target: MachineId, value: Word) {
+        let mut counts = self.counts();
+        if counts.retired[target] {
+            counts.undelivered[target].push(value);
+        } else {
+            counts.in_flight += 1;
+            let _ = self.senders[target].send(Message::Value(value));
+        }
+    }
+
+    fn block(&self) {
+        let mut counts = self.counts();
+        counts.blocked += 1;
+        self.check_deadlock(&mut counts);
+    }
+
+    fn received(&self) {
+        let mut counts = self.counts();
+        counts.blocked -= 1;
+        counts.in_flight -= 1;
+    }
+
+    /// Takes a machine that halted or failed out of the count, setting its unread input aside.
+    fn retire(&self, id: MachineId, receiver: &Receiver<Message<Word>>) {
+        let mut counts = self.counts();
+        counts.retired[id] = true;
+        counts.alive -= 1;
+        while let Ok(message) = receiver.try_recv() {
+            if let Message::Value(value) = message {
+                counts.in_flight -= 1;
+                counts.undelivered[id].push(value);
+            }
+        }
+        self.check_deadlock(&mut counts);
+    }
+
+    fn check_deadlock(&self, counts: &mut Counts<Word>) {
+        if !counts.deadlocked
+            && counts.alive > 0
+            && counts.blocked == counts.alive
+            && counts.in_flight == 0
+        {
+            counts.deadlocked = true;
+            for sender in &self.senders {
+                let _ = sender.send(Message::Shutdown);
+            }
+        }
+    }
+}
+
+/// Retires its machine when dropped, so the machine is taken out of the count however its
+/// thread ends, even by panicking, and the others don't wait for it forever.
+struct Retirement<'a, Word> {
+    id: MachineId,
+    receiver: Receiver<Message<Word>>,
+    monitor: &'a Monitor<Word>,
+}
+
+impl<Word> Drop for Retirement<'_, Word> {
+    fn drop(&mut self) {
+        self.monitor.retire(self.id, &self.receiver);
+    }
+}
+
+fn run_machine<Word>(
+    id: MachineId,
+    node: &mut Node<Word>,
+    receiver: Receiver<Message<Word>>,
+    monitor: &Monitor<Word>,
+) -> Result<(), NetworkError<Word>>
+where
+    Word: IntcodeWord,
+{
+    let retirement = Retirement {
+        id,
+        receiver,
+        monitor,
+    };
+    loop {
+        let result = node
+            .emulator
+            .run()
+            .map_err(|error| NetworkError { machine: id, error })?;
+        match result {
+            RunResult::Output(value) => {
+                node.last_output = Some(value.clone());
+                node.output_count += 1;
+                if node.links.is_empty() {
+                    node.unrouted.push(value.clone());
+                }
+                for &target in &node.links {
+                    monitor.send(target, value.clone());
+                }
+            }
+            RunResult::InputRequest => {
+                monitor.block();
+                match retirement.receiver.recv() {
+                    Ok(Message::Value(value)) => {
+                        monitor.received();
+                        node.emulator.push_input(value);
+                    }
+                    Ok(Message::Shutdown) | Err(_) => {
+                        node.state = MachineState::Blocked;
+                        break;
+                    }
+                }
+            }
+            RunResult::Halt => {
+                node.state = MachineState::Halted;
+                break;
+            }
+            result => {
+                node.state = MachineState::Stopped(result);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<Word> Network<Word>
+where
+    Word: IntcodeWord + Send + Sync,
+{
+    /// Like `run`, but with one thread per machine.
+    ///
+    /// Machines that already halted or stopped are left alone. If several machines fail, the
+    /// error of the one added first is returned. A machine that panics makes this panic too,
+    /// once the other machines have stopped.
+    ///
+    /// Words have to be `Sync` as well as `Send` because forked machines share their memory
+    /// pages through `Arc`s, so machines on different threads may read the same words.
+    pub fn run_threaded(&mut self) -> Result<NetworkReport<Word>, NetworkError<Word>> {
+        let runnable =
+            |node: &Node<Word>| matches!(node.state, MachineState::Ready | MachineState::Blocked);
+        let (senders, receivers): (Vec<_>, Vec<_>) = self.nodes.iter().map(|_| channel()).unzip();
+        let retired = self
+            .nodes
+            .iter()
+            .map(|node| !runnable(node))
+            .collect::<Vec<_>>();
+        let monitor = Monitor {
+            counts: Mutex::new(Counts {
+                alive: retired.iter().filter(|&&retired| !retired).count(),
+                blocked: 0,
+                in_flight: 0,
+                undelivered: self.nodes.iter().map(|_| Vec::new()).collect(),
+                retired,
+                deadlocked: false,
+            }),
+            senders,
+        };
+
+        let monitor_ref = &monitor;
+        let results = thread::scope(|scope| {
+            let handles = self
+                .nodes
+                .iter_mut()
+                .zip(receivers)
+                .enumerate()
+                .filter(|(_, (node, _))| runnable(node))
+                .map(|(id, (node, receiver))| {
+                    scope.spawn(move || run_machine(id, node, receiver, monitor_ref))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|payload| panic::resume_unwind(payload))
+                })
+                .collect::<Vec<_>>()
+        });
+        results.into_iter().collect::<Result<(), _>>()?;
+
+        let undelivered = monitor.counts.into_inner().unwrap().undelivered;
+        for (id, values) in undelivered.into_iter().enumerate() {
+            for value in values {
+                self.nodes[id]
+                    .emulator
+                    .try_push_input(value)
+                    .map_err(|error| NetworkError { machine: id, error })?;
+            }
+        }
+        Ok(self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::Emulator;
+
+    /// Adds `amount` to every input and outputs it, halting after `count` values.
+    fn adder(amount: i64, count: i64) -> Emulator<i64> {
+        Emulator::new(vec![
+            3, 100, 1001, 100, amount, 100, 4, 100, 1001, 101, 1, 101, 1008, 101, count, 102, 1006,
+            102, 0, 99,
+        ])
+    }
+
+    #[test]
+    fn test_matches_cooperative_ring() {
+        let build = || {
+            let mut network = Network::ring((0..50).map(|i| adder(i, 20)));
+            network.push_input(0, 0).unwrap();
+            network
+        };
+        let mut cooperative = build();
+        let mut threaded = build();
+        let expected = cooperative.run().unwrap();
+        assert_eq!(threaded.run_threaded().unwrap(), expected);
+        assert!(expected.all_halted());
+        assert_eq!(expected.machines[49].last_output, Some(20 * 49 * 50 / 2));
+    }
+
+    #[test]
+    fn test_matches_cooperative_after_halt() {
+        // the tail halts after its first value and gets two more; the head stops on its halt
+        let build = || {
+            let mut network = Network::chain(vec![adder(1, 3), adder(10, 1)]);
+            for input in 0..3 {
+                network.push_input(0, input).unwrap();
+            }
+            network
+        };
+        let mut cooperative = build();
+        let mut threaded = build();
+        cooperative.machine_mut(0).add_breakpoint(19);
+        threaded.machine_mut(0).add_breakpoint(19);
+        let expected = cooperative.run().unwrap();
+        assert_eq!(threaded.run_threaded().unwrap(), expected);
+        assert_eq!(
+            expected.machines[0].state,
+            MachineState::Stopped(RunResult::Breakpoint(19))
+        );
+        assert_eq!(expected.machines[1].state, MachineState::Halted);
+        for network in &[cooperative, threaded] {
+            assert_eq!(network.machine(1).pending_input(), 2);
+            assert_eq!(network.unrouted_outputs(1), &[11]);
+        }
+    }
+
+    #[test]
+    fn test_deadlock() {
+        let mut network = Network::ring(vec![adder(1, 5), adder(1, 5), adder(1, 5)]);
+        let report = network.run_threaded().unwrap();
+        assert!(report.is_deadlocked());
+        assert!(report
+            .machines
+            .iter()
+            .all(|machine| machine.state == MachineState::Blocked));
+    }
+
+    #[test]
+    fn test_deadlock_after_halt() {
+        // the chain's head halts after one value, leaving the tail waiting for a second one
+        let mut network = Network::chain(vec![adder(1, 1), adder(10, 2)]);
+        network.push_input(0, 0).unwrap();
+        network.push_input(0, 5).unwrap();
+        let report = network.run_threaded().unwrap();
+        assert_eq!(report.machines[0].state, MachineState::Halted);
+        assert_eq!(report.machines[1].state, MachineState::Blocked);
+        assert_eq!(network.unrouted_outputs(1), &[11]);
+        assert_eq!(network.machine(0).pending_input(), 1);
+    }
+
+    #[test]
+    fn test_error() {
+        let mut network = Network::chain(vec![adder(1, 1), Emulator::new(vec![3, 0, 42])]);
+        network.push_input(0, 5).unwrap();
+        assert_eq!(network.run_threaded().unwrap_err().machine, 1);
+    }
+
+    #[test]
+    fn test_panic() {
+        // the middle machine can't queue any input, so it panics on the first value it gets,
+        // while the last one still waits for input
+        let middle = Emulator::with_input_capacity(vec![3, 0, 4, 0, 99], 0);
+        let mut network = Network::chain(vec![adder(1, 1), middle, adder(1, 1)]);
+        network.push_input(0, 5).unwrap();
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| network.run_threaded()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Execution profiles: how often each instruction ran and each memory word was touched.
+//!
+//! Start one with `Emulator::start_profile`. The counts are exact, but the emulator sticks to
+//! the interpreter while profiling, so a profiled run is several times slower than a normal one.
+
+use super::disassembler::disassemble_at;
+use super::{Address, Emulator, IntcodeWord, Opcode};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::hash::Hash;
+
+/// How many of the hottest instructions and memory words a report lists, unless asked for
+/// another number.
+pub const DEFAULT_HOTSPOTS: usize = 20;
+
+/// Counters collected by a profiling emulator.
+///
+/// Reads are operands fetched through position or relative mode; fetching the instruction
+/// words themselves isn't counted. Writes include stored input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub executions: HashMap<Address, u64>,
+    pub opcodes: HashMap<Opcode, u64>,
+    pub reads: HashMap<Address, u64>,
+    pub writes: HashMap<Address, u64>,
+}
+
+/// The entries of `counts`, highest count first and ties in key order.
+pub fn hottest<K>(counts: &HashMap<K, u64>) -> Vec<(K, u64)>
+where
+    K: Copy + Ord + Hash,
+{
+    let mut entries: Vec<_> = counts.iter().map(|(&key, &count)| (key, count)).collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * count as f64 / total as f64
+    }
+}
+
+/// A result together with the report on the profiled run that produced it. Displays both, for
+/// solutions that show where a puzzle spends its time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profiled<T> {
+    pub result: T,
+    pub report: String,
+}
+
+impl<T> fmt::Display for Profiled<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.result, self.report)
+    }
+}
+
+impl Profile {
+    pub(super) fn count_execution(&mut self, address: Address, opcode: Opcode) {
+        *self.executions.entry(address).or_insert(0) += 1;
+        *self.opcodes.entry(opcode).or_insert(0) += 1;
+    }
+
+    pub(super) fn count_read(&mut self, address: Address) {
+        *self.reads.entry(address).or_insert(0) += 1;
+    }
+
+    pub(super) fn count_write(&mut self, address: Address) {
+        *self.writes.entry(address).or_insert(0) += 1;
+    }
+
+    pub fn instructions_executed(&self) -> u64 {
+        self.opcodes.values().sum()
+    }
+
+    /// Every memory word that was read or written, with its read and write counts, busiest
+    /// first.
+    pub fn memory_hotspots(&self) -> Vec<(Address, u64, u64)> {
+        let mut accesses: HashMap<Address, (u64, u64)> = HashMap::new();
+        for (&address, &reads) in &self.reads {
+            accesses.entry(address).or_default().0 = reads;
+        }
+        for (&address, &writes) in &self.writes {
+            accesses.entry(address).or_default().1 = writes;
+        }
+        let mut hotspots: Vec<_> = accesses
+            .into_iter()
+            .map(|(address, (reads, writes))| (address, reads, writes))
+            .collect();
+        hotspots.sort_by(|a, b| (b.1 + b.2).cmp(&(a.1 + a.2)).then(a.0.cmp(&b.0)));
+        hotspots
+    }
+
+    /// Lists the opcodes by execution count, then the `top` hottest instructions and memory
+    /// words. `read` gives the current memory, to disassemble the instruction at each address.
+    pub fn report<Word>(&self, read: impl Fn(Address) -> Word, top: usize) -> String
+    where
+        Word: IntcodeWord + fmt::Display,
+    {
+        let total = self.instructions_executed();
+        let mut report = String::new();
+        writeln!(
+            report,
+            "{} instructions executed at {} addresses",
+            total,
+            self.executions.len()
+        )
+        .unwrap();
+
+        writeln!(report, "\n{:>12} {:>7}  opcode", "count", "%").unwrap();
+        for (opcode, count) in hottest(&self.opcodes) {
+            let mnemonic = Emulator::<Word>::instruction_info(opcode as usize)
+                .unwrap()
+                .mnemonic;
+            let share = percent(count, total);
+            writeln!(report, "{:>12} {:>6.2}%  {}", count, share, mnemonic).unwrap();
+        }
+
+        writeln!(report, "\n{:>12} {:>7}  instruction", "count", "%").unwrap();
+        for (address, count) in hottest(&self.executions).into_iter().take(top) {
+            let line = &disassemble_at(&read, address, 1)[0];
+            let share = percent(count, total);
+            writeln!(report, "{:>12} {:>6.2}%  {}", count, share, line).unwrap();
+        }
+
+        writeln!(report, "\n{:>12} {:>12}  address", "reads", "writes").unwrap();
+        for (address, reads, writes) in self.memory_hotspots().into_iter().take(top) {
+            writeln!(report, "{:>12} {:>12}  {}", reads, writes, address).unwrap();
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::Emulator;
+
+    /// Counts `[13]` down from 3, outputting it on every pass.
+    const COUNTDOWN: [i64; 14] = [
+        4, 13, // 0: output [13]
+        1001, 13, -1, 13, // 2: add [13], #-1, [13]
+        1005, 13, 0,  // 6: jump_if_true [13], #0
+        99, // 9: halt
+        0, 0, 0, // 10: data
+        3, // 13: data
+    ];
+
+    fn profile_countdown() -> Profile {
+        let mut emulator = Emulator::new(COUNTDOWN.to_vec());
+        emulator.start_profile();
+        assert_eq!(emulator.run_to_completion(vec![]).unwrap(), vec![3, 2, 1]);
+        emulator.stop_profile().unwrap()
+    }
+
+    #[test]
+    fn test_counts() {
+        let profile = profile_countdown();
+        assert_eq!(profile.instructions_executed(), 10);
+        assert_eq!(
+            hottest(&profile.executions),
+            vec![(0, 3), (2, 3), (6, 3), (9, 1)]
+        );
+        assert_eq!(
+            hottest(&profile.opcodes),
+            vec![
+                (Opcode::Add, 3),
+                (Opcode::Output, 3),
+                (Opcode::JumpIfTrue, 3),
+                (Opcode::Halt, 1)
+            ]
+        );
+        assert_eq!(profile.reads, vec![(13, 9)].into_iter().collect());
+        assert_eq!(profile.writes, vec![(13, 3)].into_iter().collect());
+        assert_eq!(profile.memory_hotspots(), vec![(13, 9, 3)]);
+    }
+
+    #[test]
+    fn test_input_and_relative_mode() {
+        // add_to_relative_base #10, input rb+0, add rb+0, rb+1, rb+0, halt
+        let mut emulator = Emulator::new(vec![109, 10, 203, 0, 22201, 0, 1, 0, 99]);
+        emulator.start_profile();
+        emulator.push_input(5);
+        emulator.run().unwrap();
+        let profile = emulator.stop_profile().unwrap();
+        assert_eq!(profile.reads, vec![(10, 1), (11, 1)].into_iter().collect());
+        assert_eq!(profile.writes, vec![(10, 2)].into_iter().collect());
+        assert_eq!(emulator.profile(), None);
+    }
+
+    #[test]
+    fn test_report() {
+        let report = profile_countdown().report(|address| COUNTDOWN[address], 2);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines[0], "10 instructions executed at 4 addresses");
+        assert!(lines.contains(&"           3  30.00%  jump_if_true"));
+        assert!(lines.contains(&"           3  30.00%       0: output [13]"));
+        assert!(lines.contains(&"           3  30.00%       2: add [13], #-1, [13]"));
+        assert!(!lines
+            .iter()
+            .any(|line| line.ends_with("jump_if_true [13], #0")));
+        assert!(lines.contains(&"           9            3  13"));
+    }
+}
@@ -0,0 +1,376 @@
+//! Saving and restoring the complete state of an `Emulator`.
+//!
+//! Snapshots are plain text, starting with a version line, followed by one `key value` line
+//! per field. Memory is stored as the program image plus every page written past its end.
+
+use super::{Emulator, IntcodeWord, Memory, State, Tier, PAGE_SIZE};
+use itertools::Itertools;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const MAGIC: &str = "intcode-snapshot";
+/// Bumped whenever a field is added, since older readers reject unknown fields. Every older
+/// version still loads, with the default for fields it doesn't have.
+const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "snapshot line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for SnapshotError {}
+
+fn format_words<Word: fmt::Display>(words: &[Word]) -> String {
+    if words.is_empty() {
+        "-".to_owned()
+    } else {
+        words.iter().join(",")
+    }
+}
+
+fn format_option<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
+
+fn parse_value<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("invalid value `{}`", text))
+}
+
+fn parse_words<Word: FromStr>(text: &str) -> Result<Vec<Word>, String> {
+    match text {
+        "-" => Ok(Vec::new()),
+        text => text.split(',').map(parse_value).collect(),
+    }
+}
+
+fn parse_option<T: FromStr>(text: &str) -> Result<Option<T>, String> {
+    match text {
+        "-" => Ok(None),
+        text => parse_value(text).map(Some),
+    }
+}
+
+impl<Word> Emulator<Word>
+where
+    Word: IntcodeWord,
+{
+    /// Writes memory, registers, state and pending input to `writer`.
+    ///
+    /// Breakpoints, watchpoints and tracing are debugging aids and are not saved.
+    pub fn save_state(&self, writer: impl Write) -> io::Result<()>
+    where
+        Word: fmt::Display,
+    {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} {}", MAGIC, VERSION)?;
+        writeln!(writer, "ip {}", self.instruction_pointer)?;
+        writeln!(writer, "rb {}", self.relative_base_offset)?;
+        match &self.state {
+            State::Running => writeln!(writer, "state running")?,
+            State::Halt => writeln!(writer, "state halt")?,
+            State::RequestingInput(address) => writeln!(writer, "state input {}", address)?,
+            State::HoldingOutput(value) => writeln!(writer, "state output {}", value)?,
+        }
+        let input = self.input_buffer.iter().cloned().collect::<Vec<_>>();
+        writeln!(writer, "input {}", format_words(&input))?;
+        writeln!(
+            writer,
+            "input_capacity {}",
+            format_option(self.input_capacity)
+        )?;
+        writeln!(writer, "limit {}", format_option(self.memory.limit))?;
+        writeln!(writer, "checked_arithmetic {}", self.checked_arithmetic)?;
+        writeln!(writer, "decode_cache {}", self.decode_cache)?;
+        match self.tier {
+            Tier::Interpreter => writeln!(writer, "tier interpreter")?,
+            Tier::Compiled => writeln!(writer, "tier compiled")?,
+        }
+        writeln!(writer, "end {}", self.memory.end)?;
+        let image = self
+            .memory
+            .image
+            .iter()
+            .flat_map(|page| page.iter().cloned());
+        let image = image.take(self.memory.end).collect::<Vec<_>>();
+        writeln!(writer, "image {}", format_words(&image))?;
+        for (page, words) in self.memory.pages.iter().sorted_by_key(|(&page, _)| page) {
+            writeln!(writer, "page {} {}", page, format_words(words))?;
+        }
+        writer.flush()
+    }
+
+    /// Restores an emulator written by `save_state`.
+    pub fn load_state(reader: impl BufRead) -> Result<Self, Box<dyn Error>>
+    where
+        Word: FromStr,
+    {
+        let mut emulator = Self::new(Vec::new());
+        let mut lines = reader.lines().enumerate();
+
+        let header = match lines.next() {
+            Some((_, line)) => line?,
+            None => String::new(),
+        };
+        match header.split(' ').collect::<Vec<_>>()[..] {
+            [MAGIC, version] if (1..=VERSION).any(|known| version == known.to_string()) => {}
+            [MAGIC, version] => {
+                return Err(SnapshotError {
+                    line: 1,
+                    message: format!("unsupported snapshot version {}", version),
+                }
+                .into())
+            }
+            _ => {
+                return Err(SnapshotError {
+                    line: 1,
+                    message: "not an intcode snapshot".to_owned(),
+                }
+                .into())
+            }
+        }
+
+        for (index, line) in lines {
+            let line = line?;
+            let mut fields = line.splitn(2, ' ');
+            let key = fields.next().unwrap();
+            let value = fields.next().unwrap_or("");
+            emulator
+                .load_field(key, value)
+                .map_err(|message| SnapshotError {
+                    line: index + 1,
+                    message,
+                })?;
+        }
+        // rebuild the decode cache and compiled code for the restored memory
+        let (decode_cache, tier) = (emulator.decode_cache, emulator.tier);
+        emulator.set_decode_cache(decode_cache);
+        emulator.set_tier(tier);
+        Ok(emulator)
+    }
+
+    fn load_field(&mut self, key: &str, value: &str) -> Result<(), String>
+    where
+        Word: FromStr,
+    {
+        match key {
+            "ip" => self.instruction_pointer = parse_value(value)?,
+            "rb" => self.relative_base_offset = parse_value(value)?,
+            "state" => {
+                let mut parts = value.splitn(2, ' ');
+                self.state = match (parts.next().unwrap(), parts.next()) {
+                    ("running", None) => State::Running,
+                    ("halt", None) => State::Halt,
+                    ("input", Some(address)) => State::RequestingInput(parse_value(address)?),
+                    ("output", Some(word)) => State::HoldingOutput(parse_value(word)?),
+                    _ => return Err(format!("invalid state `{}`", value)),
+                }
+            }
+            "input" => self.input_buffer = parse_words(value)?.into_iter().collect(),
+            "input_capacity" => self.input_capacity = parse_option(value)?,
+            "limit" => self.memory.limit = parse_option(value)?,
+            "checked_arithmetic" => self.checked_arithmetic = parse_value(value)?,
+            "decode_cache" => self.decode_cache = parse_value(value)?,
+            "tier" => {
+                self.tier = match value {
+                    "interpreter" => Tier::Interpreter,
+                    "compiled" => Tier::Compiled,
+                    _ => return Err(format!("invalid tier `{}`", value)),
+                }
+            }
+            "end" => self.memory.end = parse_value(value)?,
+            "image" => {
+                let image = Memory::new(parse_words(value)?);
+                self.memory.end = self.memory.end.max(image.end);
+                self.memory.image = image.image;
+            }
+            "page" => {
+                let mut parts = value.splitn(2, ' ');
+                let page = parse_value(parts.next().unwrap())?;
+                let words = parse_words(parts.next().unwrap_or(""))?;
+                if words.len() != PAGE_SIZE {
+                    return Err(format!(
+                        "page {} has {} words, expected {}",
+                        page,
+                        words.len(),
+                        PAGE_SIZE
+                    ));
+                }
+                self.memory.pages.insert(page, Arc::new(words));
+            }
+            _ => return Err(format!("unknown field `{}`", key)),
+        }
+        Ok(())
+    }
+
+    pub fn save_state_to_file(&self, path: impl AsRef<Path>) -> io::Result<()>
+    where
+        Word: fmt::Display,
+    {
+        self.save_state(File::create(path)?)
+    }
+
+    pub fn load_state_from_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>>
+    where
+        Word: FromStr,
+    {
+        Self::load_state(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::RunResult;
+
+    fn round_trip(emulator: &Emulator<i64>) -> Emulator<i64> {
+        let mut buffer = Vec::new();
+        emulator.save_state(&mut buffer).unwrap();
+        Emulator::load_state(&buffer[..]).unwrap()
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        // echoes inputs until it reads a 0, storing each one far out of the image
+        let program = vec![3, 100, 4, 100, 1, 100, 5000, 5000, 1005, 100, 0, 99];
+        let mut emulator = Emulator::new(program);
+        emulator.extend_input(vec![3, 4]);
+        assert_eq!(emulator.run(), Ok(RunResult::Output(3)));
+
+        let mut restored = round_trip(&emulator);
+        assert_eq!(
+            restored.instruction_pointer(),
+            emulator.instruction_pointer()
+        );
+        assert_eq!(restored.pending_input(), 1);
+        for emulator in [&mut emulator, &mut restored].iter_mut() {
+            assert_eq!(emulator.run(), Ok(RunResult::Output(4)));
+            assert_eq!(emulator.run(), Ok(RunResult::InputRequest));
+        }
+
+        let mut restored = round_trip(&emulator);
+        assert_eq!(restored.read_memory(5000), 7);
+        restored.push_input(0);
+        assert_eq!(restored.run(), Ok(RunResult::Output(0)));
+        assert_eq!(restored.run(), Ok(RunResult::Halt));
+        assert!(round_trip(&restored).is_halted());
+        assert_eq!(restored.into_memory(), {
+            emulator.push_input(0);
+            while let RunResult::Output(_) = emulator.run().unwrap() {}
+            emulator.into_memory()
+        });
+    }
+
+    #[test]
+    fn test_save_holding_output() {
+        let mut emulator = Emulator::with_memory_limit(vec![104, 42, 99], 64);
+        assert_eq!(emulator.step(), Ok(RunResult::Output(42)));
+        let mut buffer = Vec::new();
+        emulator.save_state(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "intcode-snapshot 2\n\
+             ip 2\n\
+             rb 0\n\
+             state running\n\
+             input -\n\
+             input_capacity -\n\
+             limit 64\n\
+             checked_arithmetic false\n\
+             decode_cache true\n\
+             tier interpreter\n\
+             end 3\n\
+             image 104,42,99\n"
+        );
+
+        let mut emulator = Emulator::new(vec![104, 42, 99]);
+        emulator.run_instruction(&104).unwrap();
+        let mut restored = round_trip(&emulator);
+        assert_eq!(restored.run(), Ok(RunResult::Output(42)));
+    }
+
+    #[test]
+    fn test_restore_keeps_caches() {
+        let program = vec![3, 100, 1001, 100, 1, 100, 4, 100, 1105, 1, 0];
+        let mut emulator = Emulator::new(program.clone());
+        emulator.set_tier(Tier::Compiled);
+        let restored = round_trip(&emulator);
+        assert_eq!(restored.tier(), Tier::Compiled);
+        assert_eq!(restored.memory.decoded, emulator.memory.decoded);
+        assert!(restored.memory.decoded[0].is_some());
+        assert_eq!(restored.memory.code, emulator.memory.code);
+
+        let mut emulator = Emulator::new(program);
+        emulator.set_decode_cache(false);
+        let mut restored = round_trip(&emulator);
+        assert!(restored.memory.decoded.is_empty());
+        restored.push_input(1);
+        assert_eq!(restored.run(), Ok(RunResult::Output(2)));
+        assert!(restored.memory.decoded.is_empty());
+    }
+
+    #[test]
+    fn test_restore_after_far_write() {
+        // writes 7 to 2^36 and waits for input
+        let far = 1i64 << 36;
+        let mut emulator = Emulator::new(vec![1101, 3, 4, far, 3, 0, 99]);
+        assert_eq!(emulator.run(), Ok(RunResult::InputRequest));
+        let mut restored = round_trip(&emulator);
+        assert_eq!(restored.read_memory(far as usize), 7);
+        assert!(restored.memory.decoded.len() <= PAGE_SIZE);
+        restored.set_decode_cache(false);
+        restored.set_decode_cache(true);
+        assert!(restored.memory.decoded.len() <= PAGE_SIZE);
+        assert_eq!(restored.memory.decoded[..7], emulator.memory.decoded[..]);
+        restored.push_input(1);
+        assert_eq!(restored.run(), Ok(RunResult::Halt));
+    }
+
+    #[test]
+    fn test_load_version_1() {
+        let snapshot = "intcode-snapshot 1\nip 2\nrb 0\nstate running\ninput -\n\
+                        input_capacity -\nlimit -\nend 5\nimage 3,0,4,0,99\n";
+        let mut emulator = Emulator::<i64>::load_state(snapshot.as_bytes()).unwrap();
+        assert_eq!(emulator.tier(), Tier::Interpreter);
+        assert!(emulator.memory.decoded[2].is_some());
+        assert_eq!(emulator.run(), Ok(RunResult::Output(3)));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let error = |text: &str| {
+            Emulator::<i64>::load_state(text.as_bytes())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("intcode-snapshot 3\n"),
+            "snapshot line 1: unsupported snapshot version 3"
+        );
+        assert_eq!(error("hello"), "snapshot line 1: not an intcode snapshot");
+        assert_eq!(
+            error("intcode-snapshot 1\nip 0\nstate sleeping"),
+            "snapshot line 3: invalid state `sleeping`"
+        );
+        assert_eq!(
+            error("intcode-snapshot 1\ntier jit"),
+            "snapshot line 2: invalid tier `jit`"
+        );
+        assert_eq!(
+            error("intcode-snapshot 1\npage 3 1,2"),
+            "snapshot line 2: page 3 has 2 words, expected 1024"
+        );
+    }
+}
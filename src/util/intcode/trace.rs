@@ -0,0 +1,282 @@
+//! Execution traces, one line per executed instruction:
+//!
+//! ```text
+//! <ip> <mnemonic> <read operands|-> <address:value written|-> <relative base>
+//! 0 add 1,2 9:3 0
+//! 4 add_to_relative_base 4 - 4
+//! ```
+//!
+//! Read operands are listed after mode resolution, so two traces of the same program can be
+//! compared line by line to find where the runs split.
+
+use super::{Address, Emulator, InstructionInfo, IntcodeWord};
+use itertools::Itertools;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry<Word> {
+    pub instruction_pointer: Address,
+    pub instruction: &'static InstructionInfo,
+    pub operands: Vec<Word>,
+    pub write: Option<(Address, Word)>,
+    pub relative_base_offset: Word,
+}
+
+impl<Word> fmt::Display for TraceEntry<Word>
+where
+    Word: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ",
+            self.instruction_pointer, self.instruction.mnemonic
+        )?;
+        if self.operands.is_empty() {
+            write!(f, "-")?;
+        } else {
+            write!(f, "{}", self.operands.iter().format(","))?;
+        }
+        match &self.write {
+            Some((address, value)) => write!(f, " {}:{}", address, value)?,
+            None => write!(f, " -")?,
+        }
+        write!(f, " {}", self.relative_base_offset)
+    }
+}
+
+pub(super) trait TraceWriter<Word> {
+    fn write_entry(&mut self, entry: &TraceEntry<Word>) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+impl<W, Word> TraceWriter<Word> for BufWriter<W>
+where
+    W: Write,
+    Word: fmt::Display,
+{
+    fn write_entry(&mut self, entry: &TraceEntry<Word>) -> io::Result<()> {
+        writeln!(self, "{}", entry)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+}
+
+/// Per-emulator trace state. The first write error is kept and reported by `stop_trace`.
+pub(super) struct Tracer<Word> {
+    pub writer: Box<dyn TraceWriter<Word> + Send>,
+    pub operands: Vec<Word>,
+    pub pending: Option<TraceEntry<Word>>,
+    pub error: Option<io::Error>,
+}
+
+impl<Word> Tracer<Word> {
+    pub fn new(writer: Box<dyn TraceWriter<Word> + Send>) -> Self {
+        Self {
+            writer,
+            operands: Vec::new(),
+            pending: None,
+            error: None,
+        }
+    }
+
+    pub fn emit(&mut self, entry: TraceEntry<Word>) {
+        if self.error.is_none() {
+            self.error = self.writer.write_entry(&entry).err();
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(entry) = self.pending.take() {
+            self.emit(entry);
+        }
+        match self.error {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+impl<Word> fmt::Debug for Tracer<Word> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tracer")
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TraceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "trace line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for TraceParseError {}
+
+fn parse_field<T: FromStr>(field: &str) -> Result<T, String> {
+    field
+        .parse()
+        .map_err(|_| format!("invalid value `{}`", field))
+}
+
+fn parse_entry<Word>(line: &str) -> Result<TraceEntry<Word>, String>
+where
+    Word: IntcodeWord + FromStr,
+{
+    let fields = line.split(' ').collect::<Vec<_>>();
+    if fields.len() != 5 {
+        return Err(format!("expected 5 fields, found {}", fields.len()));
+    }
+    let instruction = Emulator::<Word>::INSTRUCTIONS
+        .iter()
+        .find(|info| info.mnemonic == fields[1])
+        .ok_or_else(|| format!("unknown mnemonic `{}`", fields[1]))?;
+    let operands = match fields[2] {
+        "-" => Vec::new(),
+        operands => operands
+            .split(',')
+            .map(parse_field)
+            .collect::<Result<_, _>>()?,
+    };
+    let write = match fields[3] {
+        "-" => None,
+        write => {
+            let mut parts = write.splitn(2, ':');
+            let address = parse_field(parts.next().unwrap())?;
+            let value = parts
+                .next()
+                .ok_or_else(|| format!("invalid write `{}`", write))
+                .and_then(parse_field)?;
+            Some((address, value))
+        }
+    };
+    Ok(TraceEntry {
+        instruction_pointer: parse_field(fields[0])?,
+        instruction,
+        operands,
+        write,
+        relative_base_offset: parse_field(fields[4])?,
+    })
+}
+
+/// Reads a trace written by `Emulator::trace_to`, one entry per line.
+pub fn read_trace<Word>(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<TraceEntry<Word>, Box<dyn Error>>>
+where
+    Word: IntcodeWord + FromStr,
+{
+    reader.lines().enumerate().map(|(index, line)| {
+        let line = line?;
+        parse_entry(&line).map_err(|message| {
+            TraceParseError {
+                line: index + 1,
+                message,
+            }
+            .into()
+        })
+    })
+}
+
+/// Finds the index of the first entry where two traces differ, if any.
+///
+/// A trace that is a strict prefix of the other differs at the index where it ends.
+pub fn first_divergence<Word>(
+    a: impl IntoIterator<Item = TraceEntry<Word>>,
+    b: impl IntoIterator<Item = TraceEntry<Word>>,
+) -> Option<usize>
+where
+    Word: Eq,
+{
+    a.into_iter()
+        .zip_longest(b)
+        .position(|pair| !pair.both().is_some_and(|(a, b)| a == b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::{Opcode, RunResult};
+    use std::fs::File;
+    use std::io::BufReader;
+
+    fn trace_run(name: &str, program: Vec<i64>, input: &[i64]) -> Vec<TraceEntry<i64>> {
+        let path = std::env::temp_dir().join(format!("intcode_trace_{}.txt", name));
+        let mut emulator = Emulator::new(program);
+        emulator.trace_to(File::create(&path).unwrap());
+        emulator.extend_input(input.iter().cloned());
+        while let RunResult::Output(_) = emulator.run().unwrap() {}
+        emulator.stop_trace().unwrap();
+        let entries = read_trace(BufReader::new(File::open(&path).unwrap()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+        entries
+    }
+
+    #[test]
+    fn test_trace_format() {
+        let entries = trace_run(
+            "format",
+            vec![3, 11, 109, 4, 22201, 7, -4, 0, 4, 4, 99, 0],
+            &[5],
+        );
+        let lines = entries.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "0 input - 11:5 0",
+                "2 add_to_relative_base 4 - 4",
+                "4 add 5,3 4:8 4",
+                "8 output 8 - 4",
+                "10 halt - - 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        // outputs 1 if the input equals 8, then halts
+        let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+        let a = trace_run("divergence_a", program.clone(), &[8]);
+        let b = trace_run("divergence_b", program.clone(), &[8]);
+        let c = trace_run("divergence_c", program, &[7]);
+        assert_eq!(first_divergence(a.clone(), b), None);
+        assert_eq!(first_divergence(a.clone(), c), Some(0));
+        assert_eq!(first_divergence(a.clone(), a[..2].to_vec()), Some(2));
+
+        let outputs = a
+            .iter()
+            .filter(|entry| entry.instruction.opcode == Opcode::Output)
+            .map(|entry| entry.operands[0])
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, vec![1]);
+    }
+
+    #[test]
+    fn test_read_trace_errors() {
+        let error = |text: &str| {
+            read_trace::<i64>(text.as_bytes())
+                .find_map(Result::err)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            error("0 halt - - 0\n1 jump"),
+            "trace line 2: expected 5 fields, found 2"
+        );
+        assert_eq!(error("0 nop - - 0"), "trace line 1: unknown mnemonic `nop`");
+        assert_eq!(error("0 add 1,x 3:4 0"), "trace line 1: invalid value `x`");
+    }
+}
@@ -0,0 +1,552 @@
+//! Translates an intcode program into a Rust module, for running fixed programs natively.
+//!
+//! The module defines a `Machine` that behaves like an `Emulator` running the program: `run`
+//! returns the same `RunResult`s and yields on every input request and output. Every
+//! instruction the disassembler finds becomes a few lines of Rust with its operand modes
+//! resolved, grouped into blocks that `run` dispatches between by address.
+//!
+//! The translation assumes the program never modifies its code. The machine hands its memory,
+//! registers and pending input to an `Emulator` and lets the interpreter take over for good
+//! when the program
+//!
+//! - writes to a word of a translated instruction,
+//! - jumps to an address that doesn't start a block,
+//! - uses a negative address, or writes past `DENSE_MEMORY` words,
+//! - reaches an instruction that can't be translated, like one writing to an immediate.
+//!
+//! The interpreter re-executes the instruction that caused this, so self-modifying programs
+//! still produce the right results and errors, just more slowly. Arithmetic is unchecked, like
+//! in an `Emulator` without `set_checked_arithmetic`.
+//!
+//! The generated code uses this crate's `Emulator` for that fallback, so it only compiles as a
+//! module of this crate, and only for primitive integer words.
+
+use super::disassembler::{disassemble, flow_of, format_operand, Line};
+use super::{Address, DecodedInstruction, IntcodeWord, Opcode, OperandMode};
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::fmt;
+
+/// `SELF_MODIFYING` from the tests below, translated.
+#[cfg(test)]
+#[rustfmt::skip]
+mod self_modifying;
+
+/// `WIDE_OPERANDS` from the tests below, translated.
+#[cfg(test)]
+#[rustfmt::skip]
+mod wide_operands;
+
+const WORDS_PER_LINE: usize = 12;
+
+const PRELUDE: &str = r#"#![allow(clippy::all, dead_code, unused_parens)]
+
+use crate::util::intcode::{Address, Emulator, EmulatorError, RunResult};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+"#;
+
+const MACHINE: &str = r#"
+/// Writes past this address are left to the interpreter, which allocates memory sparsely.
+const DENSE_MEMORY: Address = 1 << 20;
+
+/// Leaves the instruction at `$address` to the interpreter if `$operand` is `None`.
+macro_rules! or_interpret {
+    ($machine:ident, $address:expr, $operand:expr) => {
+        match $operand {
+            Some(operand) => operand,
+            None => {
+                $machine.instruction_pointer = $address;
+                return $machine.fall_back();
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+pub struct Machine {
+    memory: Vec<Word>,
+    code: Vec<bool>,
+    instruction_pointer: Address,
+    relative_base_offset: Word,
+    input: VecDeque<Word>,
+    halted: bool,
+    interpreter: Option<Emulator<Word>>,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        let mut code = vec![false; PROGRAM.len()];
+        for &(start, end) in CODE {
+            code[start..end].iter_mut().for_each(|word| *word = true);
+        }
+        Self {
+            memory: PROGRAM.to_vec(),
+            code,
+            instruction_pointer: 0,
+            relative_base_offset: 0,
+            input: VecDeque::new(),
+            halted: false,
+            interpreter: None,
+        }
+    }
+
+    pub fn push_input(&mut self, input: Word) {
+        match &mut self.interpreter {
+            Some(emulator) => emulator.push_input(input),
+            None => self.input.push_back(input),
+        }
+    }
+
+    /// Whether the program left the translated code and runs on the interpreter now.
+    pub fn is_interpreted(&self) -> bool {
+        self.interpreter.is_some()
+    }
+
+    /// Feeds `inputs` to the program and runs it until it halts, like
+    /// `Emulator::run_to_completion`.
+    pub fn run_to_completion(
+        &mut self,
+        inputs: impl IntoIterator<Item = Word>,
+    ) -> Result<Vec<Word>, EmulatorError<Word>> {
+        inputs.into_iter().for_each(|input| self.push_input(input));
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunResult::Output(output) => outputs.push(output),
+                RunResult::Halt => return Ok(outputs),
+                _ => {
+                    let instruction_pointer = match &self.interpreter {
+                        // the interpreter's input instruction has already moved past itself
+                        Some(emulator) => emulator.instruction_pointer() - 2,
+                        None => self.instruction_pointer,
+                    };
+                    return Err(EmulatorError::InputExhausted { instruction_pointer });
+                }
+            }
+        }
+    }
+
+    fn read(&self, address: Address) -> Word {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    fn relative(&self, offset: Word) -> Option<Address> {
+        Address::try_from(self.relative_base_offset + offset).ok()
+    }
+
+    fn writable(&self, address: Address) -> Option<Address> {
+        let code = self.code.get(address).copied().unwrap_or(false);
+        Some(address).filter(|&address| address < DENSE_MEMORY && !code)
+    }
+
+    fn store(&mut self, address: Address, value: Word) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    fn fall_back(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        let memory = std::mem::take(&mut self.memory);
+        let mut emulator = Emulator::resume(
+            memory,
+            self.instruction_pointer,
+            self.relative_base_offset,
+        );
+        emulator.extend_input(self.input.drain(..));
+        self.interpreter.get_or_insert(emulator).run()
+    }
+
+    pub fn run(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        if let Some(emulator) = &mut self.interpreter {
+            return emulator.run();
+        }
+        if self.halted {
+            return Ok(RunResult::Halt);
+        }
+        loop {
+            match self.instruction_pointer {
+"#;
+
+const MACHINE_END: &str = r#"                _ => return self.fall_back(),
+            }
+        }
+    }
+}
+"#;
+
+struct Instruction<Word> {
+    address: Address,
+    decoded: DecodedInstruction,
+    operands: Vec<Word>,
+}
+
+impl<Word> Instruction<Word> {
+    fn next(&self) -> Address {
+        self.address + self.decoded.info.size()
+    }
+
+    /// Whether execution can continue with the next instruction of the same block.
+    fn continues_block(&self) -> bool {
+        matches!(
+            self.decoded.info.opcode,
+            Opcode::Add
+                | Opcode::Mul
+                | Opcode::LessThan
+                | Opcode::Equals
+                | Opcode::AddToRelativeBase
+                | Opcode::Input
+        )
+    }
+}
+
+/// A program translated to Rust. Its `Display` implementation writes the module source.
+pub struct Translation<Word> {
+    program: Vec<Word>,
+    blocks: Vec<Vec<Instruction<Word>>>,
+    command: Option<String>,
+}
+
+impl<Word> Translation<Word> {
+    /// Names the command that regenerates the module, for its header.
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+}
+
+/// Translates `program`, see the module documentation.
+///
+/// Blocks start at address 0, at every immediate jump target, after every jump and output, at
+/// every input (which is where `run` resumes once input arrives) and after data.
+pub fn transpile<Word>(program: &[Word]) -> Translation<Word>
+where
+    Word: IntcodeWord,
+{
+    let instructions = disassemble(program)
+        .lines
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::Instruction {
+                address,
+                decoded,
+                operands,
+            } => Some(Instruction {
+                address,
+                decoded,
+                operands,
+            }),
+            Line::Data { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    let jump_targets = instructions
+        .iter()
+        .filter_map(|instruction| flow_of(&instruction.decoded, &instruction.operands).jump_target)
+        .collect::<HashSet<_>>();
+
+    let mut blocks: Vec<Vec<Instruction<Word>>> = Vec::new();
+    for instruction in instructions {
+        let continues = match blocks.last().and_then(|block| block.last()) {
+            Some(previous) => {
+                previous.continues_block()
+                    && previous.next() == instruction.address
+                    && !jump_targets.contains(&instruction.address)
+                    && instruction.decoded.info.opcode != Opcode::Input
+            }
+            None => false,
+        };
+        match blocks.last_mut() {
+            Some(block) if continues => block.push(instruction),
+            _ => blocks.push(vec![instruction]),
+        }
+    }
+    Translation {
+        program: program.to_vec(),
+        blocks,
+        command: None,
+    }
+}
+
+/// The Rust expression reading an operand, or `None` if it can't be translated.
+///
+/// Immediates are cast to `Word`, so their type doesn't fall back to `i32`; positions that
+/// aren't valid addresses are left to the interpreter, which reports them.
+fn read_operand<Word>(address: Address, value: &Word, mode: OperandMode) -> Option<String>
+where
+    Word: IntcodeWord + fmt::Display,
+{
+    match mode {
+        OperandMode::Immediate => Some(format!("({} as Word)", value)),
+        OperandMode::Position => Some(format!("self.read({})", value.to_address()?)),
+        OperandMode::Relative => Some(format!(
+            "self.read(or_interpret!(self, {}, self.relative({})))",
+            address, value
+        )),
+    }
+}
+
+/// The Rust expression for the address an operand writes to, or `None` if it can't be translated.
+fn write_operand<Word>(address: Address, value: &Word, mode: OperandMode) -> Option<String>
+where
+    Word: IntcodeWord + fmt::Display,
+{
+    match mode {
+        OperandMode::Immediate => None,
+        OperandMode::Position => Some(format!(
+            "or_interpret!(self, {}, self.writable({}))",
+            address,
+            value.to_address()?
+        )),
+        OperandMode::Relative => Some(format!(
+            "or_interpret!(self, {}, self.relative({}).and_then(|target| self.writable(target)))",
+            address, value
+        )),
+    }
+}
+
+/// The statements executing an instruction, or `None` if it has to be left to the interpreter.
+fn translate<Word>(instruction: &Instruction<Word>) -> Option<Vec<String>>
+where
+    Word: IntcodeWord + fmt::Display,
+{
+    let Instruction {
+        address,
+        decoded,
+        operands,
+    } = instruction;
+    let modes = decoded.operand_modes();
+    let read = |index: usize| read_operand(*address, &operands[index], modes[index]);
+    let write = |index: usize| write_operand(*address, &operands[index], modes[index]);
+    let binary = |operator: &str| -> Option<Vec<String>> {
+        Some(vec![
+            format!("let value = {} {} {};", read(0)?, operator, read(1)?),
+            format!("let target = {};", write(2)?),
+            "self.store(target, value);".to_owned(),
+        ])
+    };
+    let comparison = |operator: &str| -> Option<Vec<String>> {
+        Some(vec![
+            format!(
+                "let value = ({} {} {}) as Word;",
+                read(0)?,
+                operator,
+                read(1)?
+            ),
+            format!("let target = {};", write(2)?),
+            "self.store(target, value);".to_owned(),
+        ])
+    };
+    let jump = |operator: &str| -> Option<Vec<String>> {
+        Some(vec![
+            format!("if {} {} 0 {{", read(0)?, operator),
+            format!(
+                "    self.instruction_pointer = or_interpret!(self, {}, Address::try_from({}).ok());",
+                address,
+                read(1)?
+            ),
+            "    continue;".to_owned(),
+            "}".to_owned(),
+        ])
+    };
+    match decoded.info.opcode {
+        Opcode::Add => binary("+"),
+        Opcode::Mul => binary("*"),
+        Opcode::LessThan => comparison("<"),
+        Opcode::Equals => comparison("=="),
+        Opcode::JumpIfTrue => jump("!="),
+        Opcode::JumpIfFalse => jump("=="),
+        Opcode::AddToRelativeBase => {
+            Some(vec![format!("self.relative_base_offset += {};", read(0)?)])
+        }
+        Opcode::Input => Some(vec![
+            format!("let target = {};", write(0)?),
+            "match self.input.pop_front() {".to_owned(),
+            "    Some(input) => self.store(target, input),".to_owned(),
+            "    None => {".to_owned(),
+            format!("        self.instruction_pointer = {};", address),
+            "        return Ok(RunResult::InputRequest);".to_owned(),
+            "    }".to_owned(),
+            "}".to_owned(),
+        ]),
+        Opcode::Output => Some(vec![
+            format!("let value = {};", read(0)?),
+            format!("self.instruction_pointer = {};", instruction.next()),
+            "return Ok(RunResult::Output(value));".to_owned(),
+        ]),
+        Opcode::Halt => Some(vec![
+            format!("self.instruction_pointer = {};", address),
+            "self.halted = true;".to_owned(),
+            "return Ok(RunResult::Halt);".to_owned(),
+        ]),
+    }
+}
+
+impl<Word> fmt::Display for Translation<Word>
+where
+    Word: IntcodeWord + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "//! Generated by `intcode_transpile`, do not edit.")?;
+        if let Some(command) = &self.command {
+            writeln!(f, "//! Regenerate with `{}`.", command)?;
+        }
+        writeln!(f, "//!")?;
+        writeln!(f, "//! See `util::intcode::transpiler` for how it behaves.")?;
+        writeln!(f)?;
+        write!(f, "{}", PRELUDE)?;
+        writeln!(f)?;
+        writeln!(f, "pub type Word = {};", std::any::type_name::<Word>())?;
+        writeln!(f)?;
+
+        writeln!(f, "/// The translated program.")?;
+        writeln!(f, "pub const PROGRAM: &[Word] = &[")?;
+        for words in &self.program.iter().chunks(WORDS_PER_LINE) {
+            writeln!(f, "    {},", words.format(", "))?;
+        }
+        writeln!(f, "];")?;
+        writeln!(f)?;
+
+        writeln!(f, "/// Address ranges of the translated instructions.")?;
+        writeln!(f, "const CODE: &[(Address, Address)] = &[")?;
+        let mut ranges: Vec<(Address, Address)> = Vec::new();
+        for instruction in self.blocks.iter().flatten() {
+            match ranges.last_mut() {
+                Some((_, end)) if *end == instruction.address => *end = instruction.next(),
+                _ => ranges.push((instruction.address, instruction.next())),
+            }
+        }
+        for ranges in &ranges.iter().chunks(WORDS_PER_LINE / 2) {
+            let ranges = ranges.map(|(start, end)| format!("({}, {})", start, end));
+            writeln!(f, "    {},", ranges.format(", "))?;
+        }
+        writeln!(f, "];")?;
+
+        write!(f, "{}", MACHINE)?;
+        let indent = " ".repeat(20);
+        for block in &self.blocks {
+            writeln!(f, "                {} => {{", block[0].address)?;
+            let mut falls_through = true;
+            for instruction in block {
+                let operands = instruction
+                    .operands
+                    .iter()
+                    .zip(instruction.decoded.operand_modes())
+                    .map(|(value, &mode)| format!(" {}", format_operand(value, mode)))
+                    .join(",");
+                writeln!(
+                    f,
+                    "{}// {}: {}{}",
+                    indent, instruction.address, instruction.decoded.info.mnemonic, operands
+                )?;
+                match translate(instruction) {
+                    Some(statements) => {
+                        for statement in statements {
+                            writeln!(f, "{}{}", indent, statement)?;
+                        }
+                        falls_through = !matches!(
+                            instruction.decoded.info.opcode,
+                            Opcode::Output | Opcode::Halt
+                        );
+                    }
+                    None => {
+                        writeln!(
+                            f,
+                            "{}self.instruction_pointer = {};",
+                            indent, instruction.address
+                        )?;
+                        writeln!(f, "{}return self.fall_back();", indent)?;
+                        falls_through = false;
+                        break;
+                    }
+                }
+            }
+            if falls_through {
+                let next = block.last().unwrap().next();
+                writeln!(f, "{}self.instruction_pointer = {};", indent, next)?;
+            }
+            writeln!(f, "                }}")?;
+        }
+        write!(f, "{}", MACHINE_END)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intcode::RunResult;
+
+    /// Outputs 5, then rewrites that instruction to output itself.
+    const SELF_MODIFYING: [i128; 18] = [
+        104, 5, 1005, 16, 17, 1101, 0, 4, 0, 1101, 0, 1, 16, 1105, 1, 0, 0, 99,
+    ];
+
+    /// Compares an immediate above `i32::MAX` and halts.
+    const WIDE_OPERANDS: [i128; 10] = [1107, 5000000000, 1, 9, 1005, 9, 8, 99, 99, 0];
+
+    #[test]
+    fn test_blocks() {
+        let program = [
+            3, 20, // 0: input [20]
+            1001, 20, 1, 20, // 2: add [20], #1, [20]
+            1005, 20, 12, // 6: jump_if_true [20], #12
+            104, 0, 99, // 9: output #0, then halt
+            4, 20, // 12: output [20]
+            1105, 1, 0, // 14: jump_if_true #1, #0
+        ];
+        let starts = transpile(&program)
+            .blocks
+            .iter()
+            .map(|block| block[0].address)
+            .collect::<Vec<_>>();
+        assert_eq!(starts, vec![0, 9, 11, 12, 14]);
+    }
+
+    #[test]
+    fn test_untranslatable_instructions() {
+        let source = transpile::<i64>(&[1101, 1, 2, 20, 11101, 1, 2, 3, 99]).to_string();
+        assert!(source.contains(
+            "                    // 4: add #1, #2, #3\n                    \
+             self.instruction_pointer = 4;\n                    \
+             return self.fall_back();\n"
+        ));
+        assert!(source.contains("const CODE: &[(Address, Address)] = &[\n    (0, 9),\n];"));
+        assert!(source.contains("pub type Word = i64;"));
+    }
+
+    #[test]
+    fn test_generated_module_is_current() {
+        assert_eq!(
+            transpile(&SELF_MODIFYING).to_string(),
+            include_str!("transpiler/self_modifying.rs")
+        );
+        assert_eq!(
+            transpile(&WIDE_OPERANDS).to_string(),
+            include_str!("transpiler/wide_operands.rs")
+        );
+    }
+
+    #[test]
+    fn test_wide_operands() {
+        let mut machine = wide_operands::Machine::new();
+        assert_eq!(machine.run(), Ok(RunResult::Halt));
+        assert!(!machine.is_interpreted());
+
+        // a position that isn't an address is left to the interpreter
+        let source = transpile::<i128>(&[4, 1 << 64, 99]).to_string();
+        assert!(source.contains(
+            "                    // 0: output [18446744073709551616]\n                    \
+             self.instruction_pointer = 0;\n                    \
+             return self.fall_back();\n"
+        ));
+    }
+
+    #[test]
+    fn test_self_modifying_code_falls_back() {
+        let mut machine = self_modifying::Machine::new();
+        assert_eq!(machine.run(), Ok(RunResult::Output(5)));
+        assert!(!machine.is_interpreted());
+        assert_eq!(machine.run_to_completion(None), Ok(vec![1101]));
+        assert!(machine.is_interpreted());
+    }
+}
@@ -0,0 +1,184 @@
+//! Generated by `intcode_transpile`, do not edit.
+//!
+//! See `util::intcode::transpiler` for how it behaves.
+
+#![allow(clippy::all, dead_code, unused_parens)]
+
+use crate::util::intcode::{Address, Emulator, EmulatorError, RunResult};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+pub type Word = i128;
+
+/// The translated program.
+pub const PROGRAM: &[Word] = &[
+    104, 5, 1005, 16, 17, 1101, 0, 4, 0, 1101, 0, 1,
+    16, 1105, 1, 0, 0, 99,
+];
+
+/// Address ranges of the translated instructions.
+const CODE: &[(Address, Address)] = &[
+    (0, 16), (17, 18),
+];
+
+/// Writes past this address are left to the interpreter, which allocates memory sparsely.
+const DENSE_MEMORY: Address = 1 << 20;
+
+/// Leaves the instruction at `$address` to the interpreter if `$operand` is `None`.
+macro_rules! or_interpret {
+    ($machine:ident, $address:expr, $operand:expr) => {
+        match $operand {
+            Some(operand) => operand,
+            None => {
+                $machine.instruction_pointer = $address;
+                return $machine.fall_back();
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+pub struct Machine {
+    memory: Vec<Word>,
+    code: Vec<bool>,
+    instruction_pointer: Address,
+    relative_base_offset: Word,
+    input: VecDeque<Word>,
+    halted: bool,
+    interpreter: Option<Emulator<Word>>,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        let mut code = vec![false; PROGRAM.len()];
+        for &(start, end) in CODE {
+            code[start..end].iter_mut().for_each(|word| *word = true);
+        }
+        Self {
+            memory: PROGRAM.to_vec(),
+            code,
+            instruction_pointer: 0,
+            relative_base_offset: 0,
+            input: VecDeque::new(),
+            halted: false,
+            interpreter: None,
+        }
+    }
+
+    pub fn push_input(&mut self, input: Word) {
+        match &mut self.interpreter {
+            Some(emulator) => emulator.push_input(input),
+            None => self.input.push_back(input),
+        }
+    }
+
+    /// Whether the program left the translated code and runs on the interpreter now.
+    pub fn is_interpreted(&self) -> bool {
+        self.interpreter.is_some()
+    }
+
+    /// Feeds `inputs` to the program and runs it until it halts, like
+    /// `Emulator::run_to_completion`.
+    pub fn run_to_completion(
+        &mut self,
+        inputs: impl IntoIterator<Item = Word>,
+    ) -> Result<Vec<Word>, EmulatorError<Word>> {
+        inputs.into_iter().for_each(|input| self.push_input(input));
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunResult::Output(output) => outputs.push(output),
+                RunResult::Halt => return Ok(outputs),
+                _ => {
+                    let instruction_pointer = match &self.interpreter {
+                        // the interpreter's input instruction has already moved past itself
+                        Some(emulator) => emulator.instruction_pointer() - 2,
+                        None => self.instruction_pointer,
+                    };
+                    return Err(EmulatorError::InputExhausted { instruction_pointer });
+                }
+            }
+        }
+    }
+
+    fn read(&self, address: Address) -> Word {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    fn relative(&self, offset: Word) -> Option<Address> {
+        Address::try_from(self.relative_base_offset + offset).ok()
+    }
+
+    fn writable(&self, address: Address) -> Option<Address> {
+        let code = self.code.get(address).copied().unwrap_or(false);
+        Some(address).filter(|&address| address < DENSE_MEMORY && !code)
+    }
+
+    fn store(&mut self, address: Address, value: Word) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    fn fall_back(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        let memory = std::mem::take(&mut self.memory);
+        let mut emulator = Emulator::resume(
+            memory,
+            self.instruction_pointer,
+            self.relative_base_offset,
+        );
+        emulator.extend_input(self.input.drain(..));
+        self.interpreter.get_or_insert(emulator).run()
+    }
+
+    pub fn run(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        if let Some(emulator) = &mut self.interpreter {
+            return emulator.run();
+        }
+        if self.halted {
+            return Ok(RunResult::Halt);
+        }
+        loop {
+            match self.instruction_pointer {
+                0 => {
+                    // 0: output #5
+                    let value = (5 as Word);
+                    self.instruction_pointer = 2;
+                    return Ok(RunResult::Output(value));
+                }
+                2 => {
+                    // 2: jump_if_true [16], #17
+                    if self.read(16) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 2, Address::try_from((17 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 5;
+                }
+                5 => {
+                    // 5: add #0, #4, [0]
+                    let value = (0 as Word) + (4 as Word);
+                    let target = or_interpret!(self, 5, self.writable(0));
+                    self.store(target, value);
+                    // 9: add #0, #1, [16]
+                    let value = (0 as Word) + (1 as Word);
+                    let target = or_interpret!(self, 9, self.writable(16));
+                    self.store(target, value);
+                    // 13: jump_if_true #1, #0
+                    if (1 as Word) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 13, Address::try_from((0 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 16;
+                }
+                17 => {
+                    // 17: halt
+                    self.instruction_pointer = 17;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                _ => return self.fall_back(),
+            }
+        }
+    }
+}
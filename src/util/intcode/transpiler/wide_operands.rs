@@ -0,0 +1,171 @@
+//! Generated by `intcode_transpile`, do not edit.
+//!
+//! See `util::intcode::transpiler` for how it behaves.
+
+#![allow(clippy::all, dead_code, unused_parens)]
+
+use crate::util::intcode::{Address, Emulator, EmulatorError, RunResult};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+pub type Word = i128;
+
+/// The translated program.
+pub const PROGRAM: &[Word] = &[
+    1107, 5000000000, 1, 9, 1005, 9, 8, 99, 99, 0,
+];
+
+/// Address ranges of the translated instructions.
+const CODE: &[(Address, Address)] = &[
+    (0, 9),
+];
+
+/// Writes past this address are left to the interpreter, which allocates memory sparsely.
+const DENSE_MEMORY: Address = 1 << 20;
+
+/// Leaves the instruction at `$address` to the interpreter if `$operand` is `None`.
+macro_rules! or_interpret {
+    ($machine:ident, $address:expr, $operand:expr) => {
+        match $operand {
+            Some(operand) => operand,
+            None => {
+                $machine.instruction_pointer = $address;
+                return $machine.fall_back();
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+pub struct Machine {
+    memory: Vec<Word>,
+    code: Vec<bool>,
+    instruction_pointer: Address,
+    relative_base_offset: Word,
+    input: VecDeque<Word>,
+    halted: bool,
+    interpreter: Option<Emulator<Word>>,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        let mut code = vec![false; PROGRAM.len()];
+        for &(start, end) in CODE {
+            code[start..end].iter_mut().for_each(|word| *word = true);
+        }
+        Self {
+            memory: PROGRAM.to_vec(),
+            code,
+            instruction_pointer: 0,
+            relative_base_offset: 0,
+            input: VecDeque::new(),
+            halted: false,
+            interpreter: None,
+        }
+    }
+
+    pub fn push_input(&mut self, input: Word) {
+        match &mut self.interpreter {
+            Some(emulator) => emulator.push_input(input),
+            None => self.input.push_back(input),
+        }
+    }
+
+    /// Whether the program left the translated code and runs on the interpreter now.
+    pub fn is_interpreted(&self) -> bool {
+        self.interpreter.is_some()
+    }
+
+    /// Feeds `inputs` to the program and runs it until it halts, like
+    /// `Emulator::run_to_completion`.
+    pub fn run_to_completion(
+        &mut self,
+        inputs: impl IntoIterator<Item = Word>,
+    ) -> Result<Vec<Word>, EmulatorError<Word>> {
+        inputs.into_iter().for_each(|input| self.push_input(input));
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunResult::Output(output) => outputs.push(output),
+                RunResult::Halt => return Ok(outputs),
+                _ => {
+                    let instruction_pointer = match &self.interpreter {
+                        // the interpreter's input instruction has already moved past itself
+                        Some(emulator) => emulator.instruction_pointer() - 2,
+                        None => self.instruction_pointer,
+                    };
+                    return Err(EmulatorError::InputExhausted { instruction_pointer });
+                }
+            }
+        }
+    }
+
+    fn read(&self, address: Address) -> Word {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    fn relative(&self, offset: Word) -> Option<Address> {
+        Address::try_from(self.relative_base_offset + offset).ok()
+    }
+
+    fn writable(&self, address: Address) -> Option<Address> {
+        let code = self.code.get(address).copied().unwrap_or(false);
+        Some(address).filter(|&address| address < DENSE_MEMORY && !code)
+    }
+
+    fn store(&mut self, address: Address, value: Word) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    fn fall_back(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        let memory = std::mem::take(&mut self.memory);
+        let mut emulator = Emulator::resume(
+            memory,
+            self.instruction_pointer,
+            self.relative_base_offset,
+        );
+        emulator.extend_input(self.input.drain(..));
+        self.interpreter.get_or_insert(emulator).run()
+    }
+
+    pub fn run(&mut self) -> Result<RunResult<Word>, EmulatorError<Word>> {
+        if let Some(emulator) = &mut self.interpreter {
+            return emulator.run();
+        }
+        if self.halted {
+            return Ok(RunResult::Halt);
+        }
+        loop {
+            match self.instruction_pointer {
+                0 => {
+                    // 0: less_than #5000000000, #1, [9]
+                    let value = ((5000000000 as Word) < (1 as Word)) as Word;
+                    let target = or_interpret!(self, 0, self.writable(9));
+                    self.store(target, value);
+                    // 4: jump_if_true [9], #8
+                    if self.read(9) != 0 {
+                        self.instruction_pointer = or_interpret!(self, 4, Address::try_from((8 as Word)).ok());
+                        continue;
+                    }
+                    self.instruction_pointer = 7;
+                }
+                7 => {
+                    // 7: halt
+                    self.instruction_pointer = 7;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                8 => {
+                    // 8: halt
+                    self.instruction_pointer = 8;
+                    self.halted = true;
+                    return Ok(RunResult::Halt);
+                }
+                _ => return self.fall_back(),
+            }
+        }
+    }
+}
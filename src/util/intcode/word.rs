@@ -0,0 +1,105 @@
+//! The numbers an intcode machine computes with.
+//!
+//! Puzzles only ever need `i64`, but nothing in the machine depends on the width of a word:
+//! `BigInt` runs programs whose values grow without bound, and `Wrapping` words overflow the
+//! same way in debug and release builds.
+
+use super::Address;
+use num::BigInt;
+use num_traits::{CheckedAdd, CheckedMul, FromPrimitive, One, ToPrimitive, Zero};
+use std::num::Wrapping;
+use std::ops::{Add, Div, Mul, Rem};
+
+pub trait IntcodeWord:
+    Clone
+    + Eq
+    + Ord
+    + Zero
+    + One
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + FromPrimitive
+    + ToPrimitive
+{
+    /// `self + other`, or `None` if the sum doesn't fit in a word.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// `self * other`, or `None` if the product doesn't fit in a word.
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    /// The word as a memory address or opcode, or `None` if it is negative or too large.
+    fn to_address(&self) -> Option<Address> {
+        self.to_usize()
+    }
+}
+
+macro_rules! primitive_words {
+    ($($word:ty)*) => {
+        $(
+            impl IntcodeWord for $word {
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    CheckedAdd::checked_add(self, other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    CheckedMul::checked_mul(self, other)
+                }
+            }
+
+            /// Checked arithmetic still reports overflow; only unchecked arithmetic wraps.
+            impl IntcodeWord for Wrapping<$word> {
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    self.0.checked_add(other.0).map(Wrapping)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    self.0.checked_mul(other.0).map(Wrapping)
+                }
+            }
+        )*
+    };
+}
+
+primitive_words!(i16 i32 i64 i128);
+
+/// Never overflows.
+impl IntcodeWord for BigInt {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_address() {
+        assert_eq!(1234i32.to_address(), Some(1234));
+        assert_eq!((-1i64).to_address(), None);
+        assert_eq!(Wrapping(7i64).to_address(), Some(7));
+        assert_eq!((1i128 << 64).to_address(), None);
+        assert_eq!((BigInt::from(1) << 100).to_address(), None);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        assert_eq!(IntcodeWord::checked_add(&i32::MAX, &1), None);
+        assert_eq!(
+            IntcodeWord::checked_mul(&Wrapping(i64::MAX), &Wrapping(2)),
+            None
+        );
+        assert_eq!(Wrapping(i64::MAX) * Wrapping(2), Wrapping(-2));
+        let big = BigInt::from(i128::MAX);
+        assert_eq!(
+            IntcodeWord::checked_mul(&big, &big),
+            Some(BigInt::from(i128::MAX) * BigInt::from(i128::MAX))
+        );
+    }
+}